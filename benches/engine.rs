@@ -0,0 +1,121 @@
+use cardpack::Standard52;
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion, Throughput};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use rust_go_fish::deck::parse_card;
+use rust_go_fish::hand::Hand;
+use rust_go_fish::{try_run_game, GameMode, GameState, Rules};
+
+/// Players at every benchmarked table
+const PLAYERS: usize = 4;
+
+/// Games played in every iteration of the throughput benchmark
+const GAMES: u64 = 100;
+
+/// Turns after which a game is scored, sequential players can ask each other
+/// round in circles once the stock is empty
+const TURN_LIMIT: u32 = 500;
+
+/// Get a shuffled game that has not been dealt yet
+fn shuffled(game_mode: GameMode, seed: u64) -> GameState {
+    let mut game = GameState::new_with_rules(
+        vec![game_mode; PLAYERS],
+        Rules {
+            turn_limit: Some(TURN_LIMIT),
+            ..Rules::default()
+        },
+        StdRng::seed_from_u64(seed),
+    );
+    game.verbose = false;
+    game
+}
+
+/// Get a game dealt and ready for the first turn
+fn dealt(game_mode: GameMode, seed: u64) -> GameState {
+    let mut game = shuffled(game_mode, seed);
+    game.deal();
+    game.players_match_cards();
+    game
+}
+
+fn deal(c: &mut Criterion) {
+    c.bench_function("deal", |b| {
+        b.iter_batched_ref(
+            || shuffled(GameMode::Random, 1),
+            |game| game.deal(),
+            BatchSize::SmallInput,
+        )
+    });
+}
+
+fn match_pairs(c: &mut Criterion) {
+    c.bench_function("match_pairs", |b| {
+        b.iter_batched_ref(
+            || {
+                let mut game = shuffled(GameMode::Random, 1);
+                game.deal();
+                game
+            },
+            |game| game.players_match_cards(),
+            BatchSize::SmallInput,
+        )
+    });
+}
+
+fn ask_for_card(c: &mut Criterion) {
+    let setup = || {
+        let mut game = shuffled(GameMode::Random, 1);
+        game.players[0].hand = Hand::from(Standard52::pile_from_index("5C 9S").unwrap());
+        game.players[1].hand = Hand::from(Standard52::pile_from_index("5H KD 2C 7S").unwrap());
+        game
+    };
+    let mut group = c.benchmark_group("ask_for_card");
+    for (name, index) in [("handed_over", "5C"), ("go_fish", "9S")] {
+        let card = parse_card(index).unwrap();
+        group.bench_function(name, |b| {
+            b.iter_batched_ref(
+                setup,
+                |game| game.ask_for_card(0, 1, card.clone()),
+                BatchSize::SmallInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+fn run_game(c: &mut Criterion) {
+    let mut group = c.benchmark_group("run_game");
+    for game_mode in [GameMode::Random, GameMode::Sequential] {
+        group.bench_function(BenchmarkId::from_parameter(game_mode), |b| {
+            b.iter_batched_ref(|| dealt(game_mode, 1), try_run_game, BatchSize::SmallInput)
+        });
+    }
+    group.finish();
+}
+
+/// Games per second played from the shuffle to the final standings on one thread
+fn throughput(c: &mut Criterion) {
+    let mut group = c.benchmark_group("throughput");
+    group.throughput(Throughput::Elements(GAMES));
+    for game_mode in [GameMode::Random, GameMode::Sequential] {
+        group.bench_function(BenchmarkId::from_parameter(game_mode), |b| {
+            b.iter(|| {
+                for seed in 0..GAMES {
+                    let mut game = dealt(game_mode, seed);
+                    try_run_game(&mut game).unwrap();
+                }
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    deal,
+    match_pairs,
+    ask_for_card,
+    run_game,
+    throughput
+);
+criterion_main!(benches);
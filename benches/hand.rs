@@ -0,0 +1,105 @@
+use cardpack::{Card, Pile, Rank, Standard52};
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use rust_go_fish::deck::parse_rank;
+use rust_go_fish::hand::Hand;
+use std::collections::HashMap;
+
+/// A seven card hand holding two pairs
+const HAND: &str = "5C 9S KD 5H 2C 9D QS";
+
+/// Find a pair the way a hand held as a pile was searched: a map from rank to the
+/// position of its first card, rebuilt on every call
+fn pile_find_pair(pile: &Pile) -> Option<(Card, Card)> {
+    let mut first_positions: HashMap<Rank, usize> = HashMap::new();
+    for (position, card) in pile.cards().iter().enumerate() {
+        match first_positions.get(&card.rank) {
+            Some(&first) => return Some((pile.cards()[first].clone(), card.clone())),
+            None => {
+                first_positions.insert(card.rank, position);
+            }
+        }
+    }
+    None
+}
+
+/// Lay down the pairs of a hand held as a pile, sorting it by frequency first
+fn pile_match_pairs(pile: &mut Pile) -> u32 {
+    *pile = pile.sort_by_frequency();
+    let mut pairs = 0;
+    while let Some((first, second)) = pile_find_pair(pile) {
+        pile.remove_card(&first);
+        pile.remove_card(&second);
+        pairs += 1;
+    }
+    pairs
+}
+
+/// Lay down the pairs of a hand, sorting it by frequency first
+fn hand_match_pairs(hand: &mut Hand) -> u32 {
+    hand.sort_by_frequency();
+    let mut pairs = 0;
+    while hand.take_pair().is_some() {
+        pairs += 1;
+    }
+    pairs
+}
+
+fn match_pairs(c: &mut Criterion) {
+    let pile = Standard52::pile_from_index(HAND).unwrap();
+    let hand = Hand::from(pile.clone());
+    let mut group = c.benchmark_group("match_pairs");
+    group.bench_function("pile", |b| {
+        b.iter_batched_ref(|| pile.clone(), pile_match_pairs, BatchSize::SmallInput)
+    });
+    group.bench_function("hand", |b| {
+        b.iter_batched_ref(|| hand.clone(), hand_match_pairs, BatchSize::SmallInput)
+    });
+    group.finish();
+}
+
+fn rank_lookup(c: &mut Criterion) {
+    let pile = Standard52::pile_from_index(HAND).unwrap();
+    let hand = Hand::from(pile.clone());
+    // a rank the hand does not hold, the answer to most asks
+    let rank = parse_rank("7").unwrap();
+    let mut group = c.benchmark_group("rank_lookup");
+    group.bench_function("pile", |b| {
+        b.iter(|| pile.ranks().contains(&rank) && pile.cards().iter().any(|card| card.rank == rank))
+    });
+    group.bench_function("hand", |b| b.iter(|| hand.card_of_rank(rank).is_some()));
+    group.finish();
+}
+
+fn transfer(c: &mut Criterion) {
+    let pile = Standard52::pile_from_index(HAND).unwrap();
+    let hand = Hand::from(pile.clone());
+    let rank = parse_rank("9").unwrap();
+    let mut group = c.benchmark_group("transfer");
+    group.bench_function("pile", |b| {
+        b.iter_batched_ref(
+            || (pile.clone(), Pile::default()),
+            |(from, to)| {
+                let card = from.cards().iter().find(|card| card.rank == rank).cloned();
+                if let Some(card) = card.and_then(|card| from.remove_card(&card)) {
+                    to.push(card);
+                }
+            },
+            BatchSize::SmallInput,
+        )
+    });
+    group.bench_function("hand", |b| {
+        b.iter_batched_ref(
+            || (hand.clone(), Hand::new()),
+            |(from, to)| {
+                if let Some(card) = from.take_rank(rank) {
+                    to.push(card);
+                }
+            },
+            BatchSize::SmallInput,
+        )
+    });
+    group.finish();
+}
+
+criterion_group!(benches, match_pairs, rank_lookup, transfer);
+criterion_main!(benches);
@@ -0,0 +1,27 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use rust_go_fish::tables::{Table, TableManager};
+use rust_go_fish::{GameMode, Rules};
+
+/// Tables played in every iteration of the throughput benchmark
+const TABLES: usize = 64;
+
+/// Games per second played by the table manager on one thread and on every core
+fn table_throughput(c: &mut Criterion) {
+    let tables = Table::many(vec![GameMode::Random; 4], Rules::default(), 0, TABLES);
+    let mut group = c.benchmark_group("tables");
+    group.throughput(Throughput::Elements(TABLES as u64));
+    let mut thread_counts = vec![1, TableManager::default().threads];
+    thread_counts.dedup();
+    for threads in thread_counts {
+        let manager = TableManager::new(threads);
+        group.bench_with_input(
+            BenchmarkId::new("threads", threads),
+            &manager,
+            |b, manager| b.iter(|| manager.run(&tables)),
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, table_throughput);
+criterion_main!(benches);
@@ -0,0 +1,47 @@
+#![no_main]
+
+//! Drive the training environment with arbitrary action bytes and check that
+//! every step keeps the card invariants and every illegal action is refused
+
+use libfuzzer_sys::fuzz_target;
+use rust_go_fish::audit::check_invariants;
+use rust_go_fish::env::{EnvConfig, GoFishEnv, IllegalAction};
+
+fuzz_target!(|data: &[u8]| {
+    // the first byte picks the table size and the next eight the deal
+    if data.len() < 9 {
+        return;
+    }
+    let player_count = 2 + data[0] as usize % 5;
+    let mut seed = [0; 8];
+    seed.copy_from_slice(&data[1..9]);
+
+    let mut env = GoFishEnv::new(EnvConfig::new(player_count));
+    let mut observation = env.reset(u64::from_le_bytes(seed));
+    for &byte in &data[9..] {
+        let action = byte as usize;
+        match env.try_step(action) {
+            Ok((next, reward, done)) => {
+                assert!(observation.action_mask[action]);
+                assert_eq!(check_invariants(&env.game), Ok(()));
+                assert!((0.0..=1.0).contains(&reward));
+                if done {
+                    assert!(next.action_mask.iter().all(|&legal| !legal));
+                    assert_eq!(env.try_step(action), Err(IllegalAction::EpisodeOver));
+                    return;
+                }
+                observation = next;
+            }
+            Err(error) => {
+                // a masked or out of range action is refused and changes nothing
+                assert_eq!(error, IllegalAction::Masked(action));
+                assert!(!observation
+                    .action_mask
+                    .get(action)
+                    .copied()
+                    .unwrap_or(false));
+                assert_eq!(env.observation(), observation);
+            }
+        }
+    }
+});
@@ -0,0 +1,282 @@
+use crate::deck::pairing_rank;
+use crate::game::GameState;
+use cardpack::Card;
+use std::collections::HashMap;
+use std::fmt;
+
+/// A broken rule or invariant of the game
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum RuleViolation {
+    /// A card that is not part of the deck the rules describe
+    UnknownCard(Card),
+    /// A card found more often than the deck holds it
+    DuplicateCard(Card),
+    /// A card of the deck found in no hand, laid down pile or stock
+    MissingCard(Card),
+    /// A player's laid down cards do not make complete books
+    IncompleteBook { player_index: usize },
+    /// A player index outside the table
+    NoSuchPlayer(usize),
+    /// A player asked themselves
+    AskedSelf(usize),
+    /// A player asked for a rank, or a family, they hold no card of
+    RankNotHeld { player_index: usize, card: Card },
+    /// A player in Happy Families asked for a member already in their hand
+    CardAlreadyHeld { player_index: usize, card: Card },
+    /// A card was taken from a player who does not hold it
+    CardNotHeld { player_index: usize, card: Card },
+    /// A player's answer to an ask does not match their hand
+    FalseAnswer { player_index: usize, card: Card },
+}
+
+impl fmt::Display for RuleViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RuleViolation::UnknownCard(card) => write!(f, "{} is not part of the deck", card),
+            RuleViolation::DuplicateCard(card) => {
+                write!(f, "{} is in play more often than the deck holds it", card)
+            }
+            RuleViolation::MissingCard(card) => write!(f, "{} is missing from the game", card),
+            RuleViolation::IncompleteBook { player_index } => write!(
+                f,
+                "player {} laid down cards that do not make complete books",
+                player_index
+            ),
+            RuleViolation::NoSuchPlayer(player_index) => {
+                write!(f, "there is no player at index {}", player_index)
+            }
+            RuleViolation::AskedSelf(player_index) => {
+                write!(f, "player {} asked themselves", player_index)
+            }
+            RuleViolation::RankNotHeld { player_index, card } => write!(
+                f,
+                "player {} asked for {} without holding a card of its rank",
+                player_index, card
+            ),
+            RuleViolation::CardAlreadyHeld { player_index, card } => write!(
+                f,
+                "player {} asked for {} which is already in their hand",
+                player_index, card
+            ),
+            RuleViolation::CardNotHeld { player_index, card } => {
+                write!(f, "player {} does not hold {}", player_index, card)
+            }
+            RuleViolation::FalseAnswer { player_index, card } => write!(
+                f,
+                "player {} answered the ask for {} falsely",
+                player_index, card
+            ),
+        }
+    }
+}
+
+impl std::error::Error for RuleViolation {}
+
+/// Check that every card of the deck the rules describe is in play exactly as
+/// often as the deck holds it, across the stock, the hands and the laid down
+/// piles, and that every laid down pile is made of complete books
+///
+/// # Example
+///
+/// ```
+/// use rust_go_fish::audit::{check_invariants, RuleViolation};
+/// use rust_go_fish::{GameMode, GameState};
+///
+/// let mut game = GameState::new_seeded(vec![GameMode::Random; 2], 3);
+/// game.deal();
+/// assert_eq!(check_invariants(&game), Ok(()));
+///
+/// let card = game.deck.draw_first().unwrap();
+/// assert_eq!(check_invariants(&game), Err(RuleViolation::MissingCard(card)));
+/// ```
+pub fn check_invariants(game: &GameState) -> Result<(), RuleViolation> {
+    let deck = game.rules.deck.build();
+    let mut remaining: HashMap<&str, usize> = HashMap::new();
+    for card in deck.cards() {
+        *remaining.entry(card.index.as_str()).or_insert(0) += 1;
+    }
+
+    let in_play = game.deck.cards().iter().chain(
+        game.players
+            .iter()
+            .flat_map(|player| player.hand.cards().iter().chain(player.pairs.cards())),
+    );
+    for card in in_play {
+        match remaining.get_mut(card.index.as_str()) {
+            None => return Err(RuleViolation::UnknownCard(card.clone())),
+            Some(0) => return Err(RuleViolation::DuplicateCard(card.clone())),
+            Some(count) => *count -= 1,
+        }
+    }
+    if let Some(card) = deck
+        .cards()
+        .iter()
+        .find(|card| remaining[card.index.as_str()] > 0)
+    {
+        return Err(RuleViolation::MissingCard(card.clone()));
+    }
+
+    let book_size = game.rules.book_size;
+    match game.players.iter().position(|player| {
+        player.pairs.len() % book_size != 0
+            || !player
+                .pairs
+                .cards()
+                .chunks(book_size)
+                .all(|book| is_complete_book(game, book))
+    }) {
+        Some(player_index) => Err(RuleViolation::IncompleteBook { player_index }),
+        None => Ok(()),
+    }
+}
+
+/// Check that laid down cards make one book: members of one family in Happy
+/// Families, otherwise cards that pair with each other, with wild cards
+/// standing in for any of them
+fn is_complete_book(game: &GameState, book: &[Card]) -> bool {
+    if let Some(families) = &game.rules.families {
+        let family = families.family_of(&book[0]);
+        return family.is_some() && book.iter().all(|card| families.family_of(card) == family);
+    }
+    let is_wild = |card: &Card| {
+        game.rules
+            .wild
+            .as_ref()
+            .is_some_and(|wild| wild.cards.is_wild(card))
+    };
+    let mut ranks = book.iter().filter(|card| !is_wild(card)).map(pairing_rank);
+    match ranks.next() {
+        Some(rank) => ranks.all(|other| other == rank),
+        None => true,
+    }
+}
+
+/// Check that a player may ask another player for a card
+///
+/// The players must be different players at the table, and the asking player
+/// must hold a card of the rank asked for. In Happy Families they must hold a
+/// member of the family and ask for a member they do not hold.
+pub fn check_ask(
+    game: &GameState,
+    player_index: usize,
+    other_player_index: usize,
+    card: &Card,
+) -> Result<(), RuleViolation> {
+    for index in [player_index, other_player_index] {
+        if index >= game.player_count {
+            return Err(RuleViolation::NoSuchPlayer(index));
+        }
+    }
+    if player_index == other_player_index {
+        return Err(RuleViolation::AskedSelf(player_index));
+    }
+    let hand = &game.players[player_index].hand;
+    if !hand
+        .cards()
+        .iter()
+        .any(|held| pairing_rank(held) == pairing_rank(card))
+    {
+        return Err(RuleViolation::RankNotHeld {
+            player_index,
+            card: card.clone(),
+        });
+    }
+    if game.rules.families.is_some() && hand.contains(card) {
+        return Err(RuleViolation::CardAlreadyHeld {
+            player_index,
+            card: card.clone(),
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::deck::parse_card;
+    use crate::game::GameMode;
+    use crate::rules::{WildCards, WildRules};
+    use cardpack::{Pile, Standard52};
+
+    fn setup() -> GameState {
+        let mut game = GameState::new_seeded(vec![GameMode::Random; 3], 5);
+        game.verbose = false;
+        game.deal();
+        game.players_match_cards();
+        game
+    }
+
+    #[test]
+    fn test_check_invariants_finds_broken_conservation() {
+        let mut game = setup();
+        assert_eq!(check_invariants(&game), Ok(()));
+
+        let card = game.deck.cards()[0].clone();
+        game.players[0].hand.push(card.clone());
+        assert_eq!(
+            check_invariants(&game),
+            Err(RuleViolation::DuplicateCard(card))
+        );
+
+        let mut game = setup();
+        let joker = Pile::jokers().cards()[0].clone();
+        game.players[1].hand.push(joker.clone());
+        assert_eq!(
+            check_invariants(&game),
+            Err(RuleViolation::UnknownCard(joker))
+        );
+    }
+
+    #[test]
+    fn test_check_invariants_finds_incomplete_book() {
+        let mut game = setup();
+        let card = game.deck.draw_first().unwrap();
+        game.players[2].pairs.push(card);
+        assert_eq!(
+            check_invariants(&game),
+            Err(RuleViolation::IncompleteBook { player_index: 2 })
+        );
+    }
+
+    #[test]
+    fn test_check_invariants_finds_mismatched_pair() {
+        let mut game = setup();
+        // the hands hold no pairs after matching, so the first two cards differ in rank
+        let first = game.players[0].hand.remove(0);
+        let second = game.players[0].hand.remove(0);
+        game.players[2].pairs.push(first);
+        game.players[2].pairs.push(second.clone());
+        assert_eq!(
+            check_invariants(&game),
+            Err(RuleViolation::IncompleteBook { player_index: 2 })
+        );
+
+        game.rules.wild = Some(WildRules::new(WildCards::Rank(second.rank)));
+        assert_eq!(check_invariants(&game), Ok(()));
+    }
+
+    #[test]
+    fn test_check_ask() {
+        let mut game = setup();
+        game.players[0].hand = Standard52::pile_from_index("5C 9S").unwrap().into();
+        let five = parse_card("5H").unwrap();
+        let king = parse_card("KH").unwrap();
+
+        assert_eq!(check_ask(&game, 0, 1, &five), Ok(()));
+        assert_eq!(
+            check_ask(&game, 0, 0, &five),
+            Err(RuleViolation::AskedSelf(0))
+        );
+        assert_eq!(
+            check_ask(&game, 0, 3, &five),
+            Err(RuleViolation::NoSuchPlayer(3))
+        );
+        assert_eq!(
+            check_ask(&game, 0, 1, &king),
+            Err(RuleViolation::RankNotHeld {
+                player_index: 0,
+                card: king
+            })
+        );
+    }
+}
@@ -0,0 +1,234 @@
+use cardpack::{Card, Named, Pile, Rank, Standard52, BIG_JOKER, LITTLE_JOKER, TEN};
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+/// Number of players from which two decks are recommended
+pub const TWO_DECK_PLAYER_COUNT: usize = 8;
+
+/// Most decks a game may be played with
+pub const MAX_DECKS: usize = 8;
+
+/// Describes the cards a game is played with
+///
+/// # Example
+///
+/// ```
+/// use rust_go_fish::deck::{parse_rank, DeckConfig};
+///
+/// // two decks without the twos, threes and fours
+/// let config = DeckConfig {
+///     decks: 2,
+///     removed_ranks: vec![parse_rank("2").unwrap(), parse_rank("3").unwrap(), parse_rank("4").unwrap()],
+///     jokers: false,
+/// };
+/// assert_eq!(config.build().len(), 2 * 40);
+/// ```
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct DeckConfig {
+    /// Number of standard 52 card decks combined into the stock
+    pub decks: usize,
+    /// Ranks taken out of every deck, for example the low ranks for a short game
+    pub removed_ranks: Vec<Rank>,
+    /// Add the big and little joker of every deck. Two jokers make a pair and a
+    /// player asked for either joker hands over the other.
+    pub jokers: bool,
+}
+
+impl Default for DeckConfig {
+    fn default() -> Self {
+        DeckConfig {
+            decks: 1,
+            removed_ranks: Vec::new(),
+            jokers: false,
+        }
+    }
+}
+
+impl DeckConfig {
+    /// Get the recommended deck for a table size, two decks for eight or more players
+    pub fn for_players(player_count: usize) -> Self {
+        DeckConfig {
+            decks: if player_count >= TWO_DECK_PLAYER_COUNT {
+                2
+            } else {
+                1
+            },
+            ..DeckConfig::default()
+        }
+    }
+
+    /// Build the unshuffled stock
+    pub fn build(&self) -> Pile {
+        let mut cards = Vec::new();
+        for _ in 0..self.decks {
+            if self.jokers {
+                cards.extend(Pile::jokers().cards().iter().cloned());
+            }
+            cards.extend(
+                Pile::french_deck()
+                    .cards()
+                    .iter()
+                    .filter(|card| !self.removed_ranks.contains(&card.rank))
+                    .cloned(),
+            );
+        }
+        Pile::from_vector(cards)
+    }
+
+    /// Build the stock and shuffle it with the given random number generator
+    pub fn shuffled<R: Rng + ?Sized>(&self, rng: &mut R) -> Pile {
+        let mut cards = self.build().cards().clone();
+        cards.shuffle(rng);
+        Pile::from_vector(cards)
+    }
+}
+
+/// Check if a card is one of the jokers
+pub fn is_joker(card: &Card) -> bool {
+    card.rank.name.name() == BIG_JOKER || card.rank.name.name() == LITTLE_JOKER
+}
+
+/// Get the rank a card pairs up with, the big and little joker pair with each other
+///
+/// A player asked for either joker hands over the other one, so a lone joker can
+/// be paired by asking as well as by drawing.
+pub(crate) fn pairing_rank(card: &Card) -> Rank {
+    pairing_rank_of(card.rank)
+}
+
+/// Get the rank the cards of a rank pair up with, the jokers share one
+pub(crate) fn pairing_rank_of(rank: Rank) -> Rank {
+    if rank.name.name() == BIG_JOKER || rank.name.name() == LITTLE_JOKER {
+        Rank::new(BIG_JOKER)
+    } else {
+        rank
+    }
+}
+
+/// Parse a rank from its index, such as `A`, `T`, `10` or `2`
+pub fn parse_rank(index: &str) -> Option<Rank> {
+    let rank = match index {
+        "10" => Rank::new(TEN),
+        _ if index.chars().count() == 1 => Rank::from_french_deck_char(index.chars().next()?),
+        _ => return None,
+    };
+    if rank.is_blank() {
+        None
+    } else {
+        Some(rank)
+    }
+}
+
+/// Parse a card from its index, such as `AS` or `TD`
+pub fn parse_card(index: &str) -> Option<Card> {
+    let card = Standard52::card_from_string(index.to_string());
+    if card.is_valid() {
+        Some(card)
+    } else {
+        None
+    }
+}
+
+/// Parse a pile from card indexes separated by whitespace, such as `"2S 2D QS"`
+///
+/// The jokers are written `JBT` and `JLT`. Unlike `Standard52::pile_from_index`
+/// the indexes don't need to be a static string, so they can come from a file.
+///
+/// # Example
+///
+/// ```
+/// use rust_go_fish::deck::parse_pile;
+///
+/// let pile = parse_pile("2S 2D JLT").unwrap();
+/// assert_eq!(pile.len(), 3);
+/// assert!(parse_pile("2S XX").is_err());
+/// ```
+pub fn parse_pile(indexes: &str) -> Result<Pile, String> {
+    let jokers = Pile::jokers();
+    indexes
+        .split_whitespace()
+        .map(|index| {
+            jokers
+                .cards()
+                .iter()
+                .find(|joker| joker.index == index)
+                .cloned()
+                .or_else(|| parse_card(index).filter(|card| card.index == index))
+                .ok_or(format!("unknown card: {}", index))
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .map(Pile::from_vector)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cardpack::TWO;
+
+    #[test]
+    fn test_default_deck_is_standard52() {
+        let deck = DeckConfig::default().build();
+        assert_eq!(deck, Standard52::default().deck);
+    }
+
+    #[test]
+    fn test_two_decks_with_jokers() {
+        let config = DeckConfig {
+            decks: 2,
+            removed_ranks: Vec::new(),
+            jokers: true,
+        };
+        let deck = config.build();
+        assert_eq!(deck.len(), 108);
+        assert_eq!(deck.cards().iter().filter(|card| is_joker(card)).count(), 4);
+    }
+
+    #[test]
+    fn test_removed_ranks() {
+        let config = DeckConfig {
+            removed_ranks: vec![Rank::new(TWO)],
+            ..DeckConfig::default()
+        };
+        let deck = config.build();
+        assert_eq!(deck.len(), 48);
+        assert!(!deck.ranks().contains(&Rank::new(TWO)));
+    }
+
+    #[test]
+    fn test_jokers_pair_with_each_other() {
+        let jokers = Pile::jokers();
+        let (big, little) = (&jokers.cards()[0], &jokers.cards()[1]);
+        assert_eq!(pairing_rank(big), pairing_rank(little));
+        assert_eq!(pairing_rank_of(little.rank), pairing_rank(big));
+        assert_ne!(pairing_rank(big), parse_rank("A").unwrap());
+    }
+
+    #[test]
+    fn test_for_players() {
+        assert_eq!(DeckConfig::for_players(4).decks, 1);
+        assert_eq!(DeckConfig::for_players(8).decks, 2);
+    }
+
+    #[test]
+    fn test_parse_rank() {
+        assert_eq!(parse_rank("2"), Some(Rank::new(TWO)));
+        assert_eq!(parse_rank("10"), parse_rank("T"));
+        assert_eq!(parse_rank("X"), None);
+        assert_eq!(parse_rank("22"), None);
+    }
+
+    #[test]
+    fn test_parse_pile() {
+        let pile = parse_pile(" 2S  QH\nJBT ").unwrap();
+        assert_eq!(
+            pile,
+            Pile::from_vector(vec![
+                parse_card("2S").unwrap(),
+                parse_card("QH").unwrap(),
+                Pile::jokers().cards()[0].clone(),
+            ])
+        );
+        assert_eq!(parse_pile("").unwrap().len(), 0);
+        assert_eq!(parse_pile("2SX"), Err("unknown card: 2SX".to_string()));
+    }
+}
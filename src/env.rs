@@ -0,0 +1,382 @@
+use crate::game::{EndGameCondition, GameMode, GameState, PlayerTurnResult};
+use crate::rules::Rules;
+use crate::search::{legal_asks, Ask};
+use crate::view::PlayerView;
+use cardpack::{Card, Pile, Rank};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::fmt;
+
+/// Number of rank slots in an observation, the thirteen ranks and one for the jokers
+pub const RANK_SLOTS: usize = 14;
+
+/// Turn limit used by the environment when the rules have none, so every episode ends
+pub const DEFAULT_ENV_TURN_LIMIT: u32 = 1000;
+
+/// The settings of a training environment
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct EnvConfig {
+    pub player_count: usize,
+    /// The seat of the agent being trained
+    pub agent_index: usize,
+    /// How the other players pick their asks
+    pub opponent_mode: GameMode,
+    pub rules: Rules,
+}
+
+impl EnvConfig {
+    /// Create a config where the agent plays first against random opponents
+    pub fn new(player_count: usize) -> Self {
+        EnvConfig {
+            player_count,
+            agent_index: 0,
+            opponent_mode: GameMode::Random,
+            rules: Rules::default(),
+        }
+    }
+}
+
+/// What the agent sees before choosing an action
+#[derive(Debug, PartialEq, Clone)]
+pub struct Observation {
+    /// The agent's view of the game encoded by `encode_view`
+    pub features: Vec<f32>,
+    /// The legal actions, indexed like the actions passed to `step`
+    pub action_mask: Vec<bool>,
+}
+
+/// Get the slot of a card's rank in an observation, the thirteen ranks from ace
+/// down to two and then the jokers
+pub fn rank_slot(card: &Card) -> usize {
+    french_ranks()
+        .iter()
+        .position(|rank| *rank == card.rank)
+        .unwrap_or(RANK_SLOTS - 1)
+}
+
+/// Get the thirteen ranks of a french deck from ace down to two
+fn french_ranks() -> Vec<Rank> {
+    Pile::french_deck().ranks()
+}
+
+/// Get the number of features in an observation for a table size
+pub fn observation_size(player_count: usize) -> usize {
+    // hand rank counts, then per opponent the card count, known ranks and score,
+    // then the agent's score and the stock size
+    RANK_SLOTS + (player_count - 1) * (RANK_SLOTS + 2) + 2
+}
+
+/// Get the number of actions for a table size, one for every rank slot and opponent
+pub fn action_count(player_count: usize) -> usize {
+    RANK_SLOTS * (player_count - 1)
+}
+
+/// Encode a player's view as a fixed size list of numbers
+///
+/// The features are, in order: the number of cards of each rank slot in the
+/// player's hand; for every opponent, in seat order after the player, their
+/// number of cards, a one for every rank slot they are known to hold and their
+/// score; the player's score and the number of cards in the stock.
+///
+/// # Example
+///
+/// ```
+/// use rust_go_fish::env::{encode_view, observation_size};
+/// use rust_go_fish::view::PlayerView;
+/// use rust_go_fish::{GameMode, GameState};
+///
+/// let mut game = GameState::new_seeded(vec![GameMode::Random; 3], 4);
+/// game.deal();
+/// let features = encode_view(&PlayerView::from_game(&game, 0));
+/// assert_eq!(features.len(), observation_size(3));
+/// ```
+pub fn encode_view(view: &PlayerView) -> Vec<f32> {
+    let player_count = view.player_count();
+    let mut features = vec![0.0; observation_size(player_count)];
+    for card in view.hand.cards() {
+        features[rank_slot(card)] += 1.0;
+    }
+
+    let ranks = french_ranks();
+    let known = view.known_ranks();
+    for offset in 1..player_count {
+        let opponent = (view.player_index + offset) % player_count;
+        let start = RANK_SLOTS + (offset - 1) * (RANK_SLOTS + 2);
+        features[start] = view.hand_sizes[opponent] as f32;
+        for rank in &known[opponent] {
+            let slot = ranks
+                .iter()
+                .position(|other| other == rank)
+                .unwrap_or(RANK_SLOTS - 1);
+            features[start + 1 + slot] = 1.0;
+        }
+        features[start + 1 + RANK_SLOTS] = view.scores[opponent] as f32;
+    }
+
+    let end = features.len();
+    features[end - 2] = view.scores[view.player_index] as f32;
+    features[end - 1] = view.deck_size as f32;
+    features
+}
+
+/// Why the environment refused an action, the game is left as it was
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum IllegalAction {
+    /// The episode is over and needs a reset
+    EpisodeOver,
+    /// The action is not legal in the action mask
+    Masked(usize),
+}
+
+impl fmt::Display for IllegalAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IllegalAction::EpisodeOver => write!(f, "the episode is over, call reset"),
+            IllegalAction::Masked(action) => write!(f, "action {} is not legal", action),
+        }
+    }
+}
+
+impl std::error::Error for IllegalAction {}
+
+/// A gym like environment where an agent plays Go Fish against the engine's strategies
+///
+/// An action is a rank slot and an opponent, numbered
+/// `rank_slot * (player_count - 1) + opponent_offset - 1` where the opponent
+/// offset counts seats after the agent. The reward is zero until the episode
+/// ends, then one for a win, the agent's share for a tie and zero for a loss.
+///
+/// # Example
+///
+/// ```
+/// use rust_go_fish::env::{EnvConfig, GoFishEnv};
+///
+/// let mut env = GoFishEnv::new(EnvConfig::new(3));
+/// let mut observation = env.reset(7);
+/// loop {
+///     let action = observation.action_mask.iter().position(|&legal| legal).unwrap();
+///     let (next, reward, done) = env.step(action);
+///     if done {
+///         assert!((0.0..=1.0).contains(&reward));
+///         break;
+///     }
+///     observation = next;
+/// }
+/// ```
+pub struct GoFishEnv {
+    pub config: EnvConfig,
+    /// The game being played, replaced on every reset
+    pub game: GameState,
+    pub outcome: EndGameCondition,
+}
+
+impl GoFishEnv {
+    pub fn new(config: EnvConfig) -> Self {
+        let game = GoFishEnv::new_game(&config, 0);
+        GoFishEnv {
+            config,
+            game,
+            outcome: EndGameCondition::Continue,
+        }
+    }
+
+    fn new_game(config: &EnvConfig, seed: u64) -> GameState {
+        let mut game_modes = vec![config.opponent_mode; config.player_count];
+        game_modes[config.agent_index] = GameMode::Random;
+        let mut rules = config.rules.clone();
+        rules.turn_limit = rules.turn_limit.or(Some(DEFAULT_ENV_TURN_LIMIT));
+        let mut game = GameState::new_with_rules(game_modes, rules, StdRng::seed_from_u64(seed));
+        game.verbose = false;
+        game
+    }
+
+    /// Start a new episode dealt from the seed and play until it is the agent's turn
+    pub fn reset(&mut self, seed: u64) -> Observation {
+        self.game = GoFishEnv::new_game(&self.config, seed);
+        self.game.deal();
+        self.game.players_match_cards();
+        self.outcome = EndGameCondition::Continue;
+        let first_player_index = self.game.first_player_index();
+        self.play_until_agent_turn(first_player_index);
+        self.observation()
+    }
+
+    /// Make the agent's ask and play until it is the agent's turn again or the game ends
+    ///
+    /// # Panics
+    ///
+    /// If the episode is over or the action is not legal in the action mask,
+    /// see `try_step` for a version that returns an error instead
+    pub fn step(&mut self, action: usize) -> (Observation, f64, bool) {
+        self.try_step(action)
+            .unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    /// Make the agent's ask and play until it is the agent's turn again or the game ends
+    ///
+    /// An action taken after the episode is over or masked out of the action mask is
+    /// refused with an error and leaves the game unchanged.
+    pub fn try_step(&mut self, action: usize) -> Result<(Observation, f64, bool), IllegalAction> {
+        if self.outcome != EndGameCondition::Continue {
+            return Err(IllegalAction::EpisodeOver);
+        }
+        let agent_index = self.config.agent_index;
+        let ask = self
+            .ask_for_action(action)
+            .ok_or(IllegalAction::Masked(action))?;
+
+        self.game.turns += 1;
+        let turn_result =
+            self.game
+                .perform_turn(agent_index, ask.other_player_index, ask.card_index);
+        let next_player_index = match turn_result {
+            PlayerTurnResult::PlayAgain => agent_index,
+            PlayerTurnResult::NextPlayerTurn => self.game.next_player_index(agent_index),
+        };
+        self.play_until_agent_turn(next_player_index);
+
+        let reward = match &self.outcome {
+            EndGameCondition::Winner(winner) if *winner == agent_index => 1.0,
+            EndGameCondition::Tie(winners) if winners.contains(&agent_index) => {
+                1.0 / winners.len() as f64
+            }
+            _ => 0.0,
+        };
+        let done = self.outcome != EndGameCondition::Continue;
+        Ok((self.observation(), reward, done))
+    }
+
+    /// Get the agent's current observation
+    pub fn observation(&self) -> Observation {
+        let view = PlayerView::from_game(&self.game, self.config.agent_index);
+        let mut action_mask = vec![false; action_count(self.config.player_count)];
+        if self.outcome == EndGameCondition::Continue {
+            for ask in legal_asks(&view) {
+                action_mask[self.action_for_ask(&view, ask)] = true;
+            }
+        }
+        Observation {
+            features: encode_view(&view),
+            action_mask,
+        }
+    }
+
+    /// Get the action number of an ask
+    fn action_for_ask(&self, view: &PlayerView, ask: Ask) -> usize {
+        let player_count = self.config.player_count;
+        let offset = (ask.other_player_index + player_count - view.player_index) % player_count;
+        rank_slot(&view.hand.cards()[ask.card_index]) * (player_count - 1) + offset - 1
+    }
+
+    /// Get the legal ask an action stands for
+    fn ask_for_action(&self, action: usize) -> Option<Ask> {
+        let view = PlayerView::from_game(&self.game, self.config.agent_index);
+        legal_asks(&view)
+            .into_iter()
+            .find(|&ask| self.action_for_ask(&view, ask) == action)
+    }
+
+    /// Play the other players' turns, starting with the given player, until the
+    /// agent can ask or the game ends
+    fn play_until_agent_turn(&mut self, player_index: usize) {
+        let agent_index = self.config.agent_index;
+        let mut current_player_index = player_index;
+        loop {
+            self.outcome = self.game.check_win_condition();
+            if self.outcome != EndGameCondition::Continue {
+                return;
+            }
+            if current_player_index == agent_index
+                && !self.game.players[agent_index].hand.is_empty()
+            {
+                return;
+            }
+            if self.game.play_turn(current_player_index) == PlayerTurnResult::NextPlayerTurn {
+                current_player_index = self.game.next_player_index(current_player_index);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cardpack::Standard52;
+
+    #[test]
+    fn test_reset_is_repeatable() {
+        let mut env = GoFishEnv::new(EnvConfig::new(4));
+        let first = env.reset(3);
+        let second = env.reset(3);
+        assert_eq!(first, second);
+        assert_eq!(first.features.len(), observation_size(4));
+        assert_eq!(first.action_mask.len(), action_count(4));
+        assert!(first.action_mask.iter().any(|&legal| legal));
+    }
+
+    #[test]
+    fn test_action_mask_matches_hand() {
+        let mut env = GoFishEnv::new(EnvConfig::new(3));
+        env.reset(1);
+        env.game.players[0].hand = Standard52::pile_from_index("AS 2C").unwrap().into();
+        let observation = env.observation();
+
+        let legal: Vec<usize> = (0..action_count(3))
+            .filter(|&action| observation.action_mask[action])
+            .collect();
+        // aces are slot 0 and twos slot 12, each can be asked of either opponent
+        assert_eq!(legal, vec![0, 1, 24, 25]);
+        assert_eq!(observation.features[0], 1.0);
+        assert_eq!(observation.features[12], 1.0);
+    }
+
+    #[test]
+    fn test_episode_ends_with_reward() {
+        let mut config = EnvConfig::new(3);
+        config.agent_index = 1;
+        let mut env = GoFishEnv::new(config);
+        let mut observation = env.reset(5);
+        for _ in 0..DEFAULT_ENV_TURN_LIMIT {
+            let action = observation
+                .action_mask
+                .iter()
+                .position(|&legal| legal)
+                .unwrap();
+            let (next, reward, done) = env.step(action);
+            if done {
+                assert!((0.0..=1.0).contains(&reward));
+                assert!(next.action_mask.iter().all(|&legal| !legal));
+                return;
+            }
+            assert_eq!(reward, 0.0);
+            observation = next;
+        }
+        panic!("the episode did not end");
+    }
+
+    #[test]
+    #[should_panic(expected = "not legal")]
+    fn test_step_illegal_action_panics() {
+        let mut env = GoFishEnv::new(EnvConfig::new(2));
+        env.reset(2);
+        env.game.players[0].hand = Standard52::pile_from_index("AS").unwrap().into();
+        env.step(5);
+    }
+
+    #[test]
+    fn test_try_step_refuses_illegal_actions() {
+        let mut env = GoFishEnv::new(EnvConfig::new(2));
+        env.reset(2);
+        env.game.players[0].hand = Standard52::pile_from_index("AS").unwrap().into();
+        let before = env.observation();
+        assert_eq!(env.try_step(5), Err(IllegalAction::Masked(5)));
+        assert_eq!(
+            env.try_step(usize::MAX),
+            Err(IllegalAction::Masked(usize::MAX))
+        );
+        assert_eq!(env.observation(), before);
+
+        env.outcome = EndGameCondition::Winner(1);
+        assert_eq!(env.try_step(0), Err(IllegalAction::EpisodeOver));
+    }
+}
@@ -0,0 +1,185 @@
+use crate::deck::DeckConfig;
+use cardpack::{Card, Pile, Rank};
+
+/// Number of members in a family, a family is complete when one player holds all of them
+pub const FAMILY_SIZE: usize = 4;
+
+/// Titles of the four members of every family, in suit order (spades, hearts, diamonds, clubs)
+const MEMBER_TITLES: [&str; FAMILY_SIZE] = ["Mr", "Mrs", "Master", "Miss"];
+
+/// Number of classic families, one for each rank of the deck
+pub const CLASSIC_FAMILY_COUNT: usize = 13;
+
+/// The classic Happy Families
+const CLASSIC_FAMILIES: [(&str, &str); CLASSIC_FAMILY_COUNT] = [
+    ("Bun", "the Baker"),
+    ("Bones", "the Butcher"),
+    ("Pots", "the Painter"),
+    ("Soot", "the Sweep"),
+    ("Grits", "the Grocer"),
+    ("Chip", "the Carpenter"),
+    ("Dose", "the Doctor"),
+    ("Bung", "the Brewer"),
+    ("Block", "the Barber"),
+    ("Dip", "the Dyer"),
+    ("Tape", "the Tailor"),
+    ("Mug", "the Milkman"),
+    ("Bumps", "the Bailiff"),
+];
+
+/// A family of four named members
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Family {
+    pub name: String,
+    pub members: Vec<String>,
+}
+
+impl Family {
+    /// Create a family whose members are Mr, Mrs, Master and Miss of the surname
+    pub fn new(surname: &str, trade: &str) -> Self {
+        Family {
+            name: format!("{} {}", surname, trade),
+            members: MEMBER_TITLES
+                .iter()
+                .map(|title| format!("{} {}", title, surname))
+                .collect(),
+        }
+    }
+}
+
+/// The card set of a Happy Families game
+///
+/// The engine plays with a standard deck, so every family stands for a rank
+/// and every member for a suit. The first family is the aces, the second the
+/// kings and so on, and the members follow the suits spades, hearts, diamonds
+/// and clubs. Ranks without a family are left out of the deck.
+///
+/// # Example
+///
+/// ```
+/// use rust_go_fish::families::Families;
+/// use rust_go_fish::deck::parse_card;
+///
+/// let families = Families::default();
+/// let card = parse_card("AH").unwrap();
+/// assert_eq!(families.card_name(&card), Some("Mrs Bun".to_string()));
+/// assert_eq!(families.family_name(&card), Some("Bun the Baker"));
+/// ```
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Families {
+    pub families: Vec<Family>,
+}
+
+impl Default for Families {
+    fn default() -> Self {
+        Families {
+            families: CLASSIC_FAMILIES
+                .iter()
+                .map(|(surname, trade)| Family::new(surname, trade))
+                .collect(),
+        }
+    }
+}
+
+impl Families {
+    /// Create a card set with the first `count` classic families
+    pub fn classic(count: usize) -> Self {
+        let mut families = Families::default();
+        families.families.truncate(count);
+        families
+    }
+
+    /// Get the deck holding the four members of every family
+    pub fn deck_config(&self) -> DeckConfig {
+        DeckConfig {
+            removed_ranks: Pile::french_deck()
+                .ranks()
+                .into_iter()
+                .skip(self.families.len())
+                .collect(),
+            ..DeckConfig::default()
+        }
+    }
+
+    /// Get the family a card belongs to
+    pub fn family_of(&self, card: &Card) -> Option<&Family> {
+        let position = Pile::french_deck()
+            .ranks()
+            .iter()
+            .position(|rank| *rank == card.rank)?;
+        self.families.get(position)
+    }
+
+    /// Get the name of the family a card belongs to
+    pub fn family_name(&self, card: &Card) -> Option<&str> {
+        self.family_of(card).map(|family| family.name.as_str())
+    }
+
+    /// Get the name of the member a card stands for
+    pub fn card_name(&self, card: &Card) -> Option<String> {
+        let family = self.family_of(card)?;
+        let position = Pile::french_deck()
+            .suits()
+            .iter()
+            .position(|suit| *suit == card.suit)?;
+        family.members.get(position).cloned()
+    }
+
+    /// Get the member cards of the family with the given rank, in member order
+    pub fn members(&self, rank: Rank) -> Vec<Card> {
+        Pile::french_deck()
+            .cards()
+            .iter()
+            .filter(|card| card.rank == rank)
+            .cloned()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::deck::parse_card;
+
+    #[test]
+    fn test_classic_families_fill_the_deck() {
+        let families = Families::default();
+        assert_eq!(families.families.len(), 13);
+        assert_eq!(families.deck_config().build().len(), 52);
+    }
+
+    #[test]
+    fn test_fewer_families_shorten_the_deck() {
+        let families = Families::classic(4);
+        let deck = families.deck_config().build();
+        assert_eq!(deck.len(), 4 * FAMILY_SIZE);
+        assert!(deck
+            .cards()
+            .iter()
+            .all(|card| families.family_of(card).is_some()));
+    }
+
+    #[test]
+    fn test_card_name() {
+        let families = Families::default();
+        let card = parse_card("2C").unwrap();
+        assert_eq!(families.card_name(&card), Some("Miss Bumps".to_string()));
+        assert_eq!(families.family_name(&card), Some("Bumps the Bailiff"));
+        assert_eq!(Families::classic(2).card_name(&card), None);
+    }
+
+    #[test]
+    fn test_members_are_in_member_order() {
+        let families = Families::default();
+        let card = parse_card("KD").unwrap();
+        let names: Vec<String> = families
+            .members(card.rank)
+            .iter()
+            .filter_map(|member| families.card_name(member))
+            .collect();
+        assert_eq!(
+            names,
+            vec!["Mr Bones", "Mrs Bones", "Master Bones", "Miss Bones"]
+        );
+    }
+}
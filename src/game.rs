@@ -0,0 +1,1941 @@
+use crate::audit::{check_ask, check_invariants, RuleViolation};
+use crate::deck::pairing_rank;
+use crate::hand::Hand;
+use crate::rules::{EmptyHand, Rules, TieBreaker, WildCards, WildRules};
+use crate::search::{choose_ask, SearchConfig};
+use crate::standings::Standings;
+use crate::view::PlayerView;
+use crate::{get_random_excluding_from, get_random_from};
+use cardpack::{Card, Pile, Rank, Standard52};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum GameMode {
+    Random,
+    Sequential,
+    /// Pick the ask that wins most often in rollouts of sampled hidden hands
+    Search,
+}
+
+impl fmt::Display for GameMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            GameMode::Random => "random",
+            GameMode::Sequential => "sequential",
+            GameMode::Search => "search",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for GameMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "random" => Ok(GameMode::Random),
+            "sequential" => Ok(GameMode::Sequential),
+            "search" => Ok(GameMode::Search),
+            _ => Err(format!("unknown game mode: {}", s)),
+        }
+    }
+}
+//
+// #[derive(Debug, PartialEq, Eq, Hash, Clone)]
+// enum EndGameConditions {
+//     FirstPlayerWithNoCards,
+//     DeckEmpty,
+// }
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Serialize, Deserialize)]
+pub enum EndGameCondition {
+    Winner(usize),
+    Tie(Vec<usize>),
+    Continue,
+}
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+pub enum PlayerTurnResult {
+    NextPlayerTurn,
+    PlayAgain,
+}
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+pub enum AskCardResult {
+    ReceiveCard(Card),
+    GoFish,
+}
+
+/// A public record of an ask, seen by every player at the table
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct AskRecord {
+    /// The turn the ask was made on
+    pub turn: u32,
+    pub asker: usize,
+    pub asked: usize,
+    /// The card asked for, only its rank matters outside Happy Families
+    pub card: Card,
+    /// Number of cards handed over
+    pub received: usize,
+    /// The asker drew from the stock, the drawn card is only seen by the asker
+    pub went_fishing: bool,
+    /// Cards the asker laid down after the ask
+    pub laid_down: Vec<Card>,
+}
+
+/// Something that happened in a game, recorded in the order it happened
+///
+/// Unlike the ask records the events include what only some players saw,
+/// such as the dealt hands and the cards drawn from the stock.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum GameEvent {
+    /// A player was dealt their starting hand
+    Dealt {
+        player_index: usize,
+        cards: Vec<Card>,
+    },
+    /// A player asked another player for a card
+    Asked {
+        asker: usize,
+        asked: usize,
+        card: Card,
+    },
+    /// The asked player handed cards over to the asker
+    HandedOver {
+        from: usize,
+        to: usize,
+        cards: Vec<Card>,
+    },
+    /// The asked player had nothing to hand over
+    GoFish { player_index: usize },
+    /// A player drew a card from the stock
+    Drew { player_index: usize, card: Card },
+    /// A player laid down a pair, or a complete book
+    LaidDown {
+        player_index: usize,
+        cards: Vec<Card>,
+    },
+    /// A player without cards sat their turn out
+    SatOut { player_index: usize },
+}
+
+/// Get the cards as an index string such as "5C 9S"
+pub(crate) fn card_indices(cards: &[Card]) -> String {
+    cards
+        .iter()
+        .map(|card| card.index.as_str())
+        .collect::<Vec<&str>>()
+        .join(" ")
+}
+
+impl fmt::Display for GameEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GameEvent::Dealt {
+                player_index,
+                cards,
+            } => write!(
+                f,
+                "player {} is dealt {}",
+                player_index,
+                card_indices(cards)
+            ),
+            GameEvent::Asked { asker, asked, card } => {
+                write!(
+                    f,
+                    "player {} asks player {} for {}",
+                    asker, asked, card.index
+                )
+            }
+            GameEvent::HandedOver { from, to, cards } => write!(
+                f,
+                "player {} hands player {} {}",
+                from,
+                to,
+                card_indices(cards)
+            ),
+            GameEvent::GoFish { player_index } => write!(f, "player {} goes fish", player_index),
+            GameEvent::Drew { player_index, card } => {
+                write!(f, "player {} draws {}", player_index, card.index)
+            }
+            GameEvent::LaidDown {
+                player_index,
+                cards,
+            } => write!(
+                f,
+                "player {} lays down {}",
+                player_index,
+                card_indices(cards)
+            ),
+            GameEvent::SatOut { player_index } => write!(f, "player {} sits out", player_index),
+        }
+    }
+}
+
+/// Find the first two cards in the pile that make a pair
+///
+/// The pile may hold identical cards when several decks are combined,
+/// so cards are told apart by their position rather than by value.
+pub fn find_pair(pile: &Pile) -> Option<(Card, Card)> {
+    let mut hand = Hand::new();
+    for card in pile.cards() {
+        hand.push(card.clone());
+        if let Some(pair) = hand.take_pair() {
+            return Some(pair);
+        }
+    }
+    None
+}
+
+/// Find two cards in the pile that make a pair when some cards are wild
+///
+/// Natural pairs are found first. Otherwise the highest ranked card left
+/// on its own is paired with a wild card, and two wild cards pair with each other.
+pub fn find_pair_with_wilds(pile: &Pile, wild_cards: &WildCards) -> Option<(Card, Card)> {
+    let (wilds, naturals): (Vec<Card>, Vec<Card>) = pile
+        .cards()
+        .iter()
+        .cloned()
+        .partition(|card| wild_cards.is_wild(card));
+    if let Some(pair) = find_pair(&Pile::from_vector(naturals.clone())) {
+        return Some(pair);
+    }
+    let wild = wilds.first()?.clone();
+    match naturals.iter().max_by_key(|card| card.rank.weight) {
+        Some(single) => Some((single.clone(), wild)),
+        None => wilds.get(1).map(|other| (wild, other.clone())),
+    }
+}
+
+/// Find the first set of `size` cards of the same rank in the pile, such as a
+/// complete family in Happy Families
+pub fn find_book(pile: &Pile, size: usize) -> Option<Vec<Card>> {
+    let mut hand = Hand::new();
+    for card in pile.cards() {
+        hand.push(card.clone());
+        if let Some(book) = hand.take_book(size) {
+            return Some(book);
+        }
+    }
+    None
+}
+
+#[derive(Clone)]
+pub struct Player {
+    /// How this player picks the card to ask for and the player to ask
+    pub game_mode: GameMode,
+    pub hand: Hand,
+    pub pairs: Pile,
+    pub score: u32,
+    /// Number of asks that were answered with a card
+    pub successful_asks: u32,
+    /// Number of asks that ended in go fish
+    pub go_fishes: u32,
+}
+
+impl Default for Player {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Player {
+    pub fn new() -> Self {
+        Player::new_with_mode(GameMode::Random)
+    }
+
+    /// Create a player that plays with the given game mode
+    pub fn new_with_mode(game_mode: GameMode) -> Self {
+        Player {
+            game_mode,
+            hand: Hand::new(),
+            pairs: Pile::default(),
+            score: 0,
+            successful_asks: 0,
+            go_fishes: 0,
+        }
+    }
+
+    /// Add a card to the player's hand
+    pub fn add_card(&mut self, card: Card) {
+        self.hand.push(card);
+    }
+
+    /// Add cards to the player's hand
+    pub fn add_cards(&mut self, cards: Pile) {
+        self.hand.append(&cards);
+    }
+
+    /// Provide a answer with the card of the given rank
+    /// for the player asking it
+    /// A player asked for either joker answers with the other one
+    pub fn answer_for_card_rank(&self, rank: Rank) -> Option<Card> {
+        self.hand.card_of_rank(rank).cloned()
+    }
+
+    /// Provide the exact card asked for, if the player holds it
+    pub fn answer_for_card(&self, card: &Card) -> Option<Card> {
+        self.hand.cards().iter().find(|&c| c == card).cloned()
+    }
+
+    /// Match complete books of `size` cards in the player's hand
+    /// Remove the books from the hand and add them to the pairs pile
+    /// Return the number of books found
+    pub fn match_books(&mut self, size: usize) -> u32 {
+        let mut books_found_count = 0;
+        while let Some(book) = self.hand.take_book(size) {
+            for card in book {
+                self.pairs.push(card);
+            }
+            self.score += 1;
+            books_found_count += 1;
+        }
+        books_found_count
+    }
+
+    /// Match pairs in the player's hand
+    /// Remove the pairs from the hand and add them to the pairs pile
+    /// Return the number of pairs found
+    pub fn match_pairs(&mut self) -> u32 {
+        // Check for pairs
+        // If pair, move to pairs pile and add to the score
+        self.hand.sort_by_frequency();
+        let mut pairs_found_count = 0;
+        while let Some((card1, card2)) = self.hand.take_pair() {
+            self.pairs.push(card1);
+            self.pairs.push(card2);
+            self.score += 1;
+            pairs_found_count += 1;
+        }
+        pairs_found_count
+    }
+
+    /// Match pairs in the player's hand, using wild cards to complete pairs
+    /// Pairs completed with a wild card add the wild pair score instead of one
+    /// Return the number of pairs found
+    pub fn match_pairs_with_wilds(&mut self, wild: &WildRules) -> u32 {
+        self.hand.sort_by_frequency();
+        let mut pairs_found_count = 0;
+        while let Some((card1, card2)) = find_pair_with_wilds(&self.hand.to_pile(), &wild.cards) {
+            self.hand.remove_card(&card1);
+            self.hand.remove_card(&card2);
+            self.score += if wild.cards.is_wild(&card1) || wild.cards.is_wild(&card2) {
+                wild.wild_pair_score
+            } else {
+                1
+            };
+            self.pairs.push(card1);
+            self.pairs.push(card2);
+            pairs_found_count += 1;
+        }
+        pairs_found_count
+    }
+}
+
+#[derive(Clone)]
+pub struct GameState {
+    /// The stock players draw from when they go fish
+    pub deck: Pile,
+    pub players: Vec<Player>,
+    pub player_count: usize,
+    pub rules: Rules,
+    /// The player dealing this hand, play starts with the player after the dealer
+    pub dealer_index: usize,
+    /// Indices of the players in the order they ran out of cards
+    pub went_out: Vec<usize>,
+    /// Source of randomness for the random game mode
+    pub rng: StdRng,
+    /// Number of turns played so far, every ask is a turn
+    pub turns: u32,
+    /// Every ask made so far, in order
+    pub asks: Vec<AskRecord>,
+    /// Everything that happened so far, in order
+    pub events: Vec<GameEvent>,
+    /// Print the turn by turn narration of the game
+    pub verbose: bool,
+    /// Check the card invariants after every turn and report a broken one as an error
+    pub audit: bool,
+}
+
+impl GameState {
+    /// Create a new game with a shuffled deck
+    pub fn new(player_count: usize) -> Self {
+        let rng = StdRng::from_entropy();
+        GameState::new_with_rules(vec![GameMode::Random; player_count], Rules::default(), rng)
+    }
+
+    /// Create a new game with a specific deck
+    pub fn new_with_deck(player_count: usize, deck: Standard52, game_mode: GameMode) -> Self {
+        GameState::new_with_modes(
+            vec![game_mode; player_count],
+            deck.deck,
+            StdRng::from_entropy(),
+        )
+    }
+
+    /// Create a new game where the deck is shuffled and every random choice is made
+    /// from the given seed, so the same seed and game modes always play the same game
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rust_go_fish::{GameMode, GameState};
+    ///
+    /// let first = GameState::new_seeded(vec![GameMode::Random; 3], 7);
+    /// let second = GameState::new_seeded(vec![GameMode::Random; 3], 7);
+    /// assert_eq!(first.deck, second.deck);
+    /// ```
+    pub fn new_seeded(game_modes: Vec<GameMode>, seed: u64) -> Self {
+        GameState::new_with_rules(game_modes, Rules::default(), StdRng::seed_from_u64(seed))
+    }
+
+    /// Create a new game played by the given rules, with the deck described by the rules
+    /// shuffled by the given random number generator
+    ///
+    /// A game with sequential players gets a turn limit when the rules have none,
+    /// see [`Rules::turn_limit_for`].
+    pub fn new_with_rules(game_modes: Vec<GameMode>, rules: Rules, mut rng: StdRng) -> Self {
+        let deck = rules.deck.shuffled(&mut rng);
+        let turn_limit = rules.turn_limit_for(&game_modes);
+        let mut game = GameState::new_with_modes(game_modes, deck, rng);
+        game.rules = Rules {
+            turn_limit,
+            ..rules
+        };
+        game
+    }
+
+    /// Create a new game with one player for each game mode
+    ///
+    /// A game with sequential players gets a turn limit, see [`Rules::turn_limit_for`].
+    pub fn new_with_modes(game_modes: Vec<GameMode>, deck: Pile, rng: StdRng) -> Self {
+        let player_count = game_modes.len();
+        let rules = Rules::default();
+        let turn_limit = rules.turn_limit_for(&game_modes);
+        // create players
+        let players = game_modes.into_iter().map(Player::new_with_mode).collect();
+        GameState {
+            deck,
+            players,
+            player_count,
+            rules: Rules {
+                turn_limit,
+                ..rules
+            },
+            dealer_index: player_count.saturating_sub(1),
+            went_out: Vec::new(),
+            rng,
+            turns: 0,
+            asks: Vec::new(),
+            events: Vec::new(),
+            verbose: true,
+            audit: false,
+        }
+    }
+
+    /// Create a game in progress from what a player can see and a guess at the
+    /// cards they can't see: the hand of every player and the stock
+    ///
+    /// The player's own hand in `hands` should be the hand in the view. The
+    /// narration of the created game is turned off.
+    pub fn from_view(view: &PlayerView, hands: Vec<Pile>, deck: Pile, rng: StdRng) -> Self {
+        let mut game = GameState::new_with_modes(view.game_modes.clone(), deck, rng);
+        for (index, (player, hand)) in game.players.iter_mut().zip(hands).enumerate() {
+            player.hand = hand.into();
+            player.pairs = view.pairs[index].clone();
+            player.score = view.scores[index];
+        }
+        game.rules = view.rules.clone();
+        game.dealer_index = view.dealer_index;
+        game.went_out = view.went_out.clone();
+        game.turns = view.turns;
+        game.asks = view.asks.clone();
+        game.verbose = false;
+        game
+    }
+
+    /// Deal cards to each player, starting with the player after the dealer
+    /// The number of cards comes from the hand size rules for the table
+    /// Dealing stops early if the deck runs out
+    pub fn deal(&mut self) {
+        let hand_size = self.rules.hand_size_for(self.player_count);
+        let first_player_index = self.first_player_index();
+        'dealing: for _ in 0..hand_size {
+            for offset in 0..self.player_count {
+                let player_index = (first_player_index + offset) % self.player_count;
+                match self.deck.draw(1) {
+                    Some(card) => self.players[player_index].add_cards(card),
+                    None => break 'dealing,
+                }
+            }
+        }
+        for offset in 0..self.player_count {
+            let player_index = (first_player_index + offset) % self.player_count;
+            let cards = self.players[player_index].hand.cards().clone();
+            self.events.push(GameEvent::Dealt {
+                player_index,
+                cards,
+            });
+        }
+    }
+
+    /// Get everything that happened so far, one event per line
+    pub fn transcript(&self) -> String {
+        self.events
+            .iter()
+            .map(|event| format!("{}\n", event))
+            .collect()
+    }
+
+    /// Match cards in each player's hand
+    pub fn players_match_cards(&mut self) {
+        for player_index in 0..self.player_count {
+            let _pairs_found = self.match_player_pairs(player_index);
+        }
+    }
+
+    /// Lay down the pairs in a player's hand, using wild cards if the rules have them,
+    /// or the complete books when the rules lay down more than two cards at a time
+    fn match_player_pairs(&mut self, player_index: usize) -> u32 {
+        let player = &mut self.players[player_index];
+        let laid_down = player.pairs.len();
+        let found = if self.rules.book_size != 2 {
+            player.match_books(self.rules.book_size)
+        } else {
+            match &self.rules.wild {
+                Some(wild) => player.match_pairs_with_wilds(wild),
+                None => player.match_pairs(),
+            }
+        };
+        let books: Vec<Vec<Card>> = player.pairs.cards()[laid_down..]
+            .chunks(self.rules.book_size)
+            .map(|book| book.to_vec())
+            .collect();
+        for cards in books {
+            self.events.push(GameEvent::LaidDown {
+                player_index,
+                cards,
+            });
+        }
+        found
+    }
+
+    /// Get the card a player asks for when they pick a card from their hand
+    ///
+    /// In Go Fish that card's rank is asked for. In Happy Families the player asks
+    /// for a member of the card's family missing from their hand, the first one in
+    /// sequential mode and a random one otherwise.
+    fn card_to_ask_for(&mut self, player_index: usize, card: Card) -> Card {
+        let missing: Vec<Card> = match &self.rules.families {
+            Some(families) => families
+                .members(card.rank)
+                .into_iter()
+                .filter(|member| !self.players[player_index].hand.contains(member))
+                .collect(),
+            None => return card,
+        };
+        match (self.players[player_index].game_mode, missing.len()) {
+            (_, 0) => card,
+            (GameMode::Sequential, _) => missing[0].clone(),
+            (GameMode::Random | GameMode::Search, count) => {
+                missing[get_random_from(&mut self.rng, 0..count)].clone()
+            }
+        }
+    }
+
+    /// Get the name of a card for the narration, the member name in Happy Families
+    fn card_name(&self, card: &Card) -> String {
+        self.rules
+            .families
+            .as_ref()
+            .and_then(|families| families.card_name(card))
+            .unwrap_or_else(|| card.to_string())
+    }
+
+    /// Get the positions of the cards in a player's hand worth asking for
+    ///
+    /// Wild cards pair with anything, so asking for their rank is a wasted ask
+    /// unless the hand holds nothing else.
+    fn askable_card_indices(&self, player_index: usize) -> Vec<usize> {
+        let cards = self.players[player_index].hand.cards();
+        let naturals: Vec<usize> = match &self.rules.wild {
+            Some(wild) if self.rules.families.is_none() => (0..cards.len())
+                .filter(|&index| !wild.cards.is_wild(&cards[index]))
+                .collect(),
+            _ => (0..cards.len()).collect(),
+        };
+        if naturals.is_empty() {
+            (0..cards.len()).collect()
+        } else {
+            naturals
+        }
+    }
+
+    /// Get the index of the next player
+    /// If the player is the last player, return the first player
+    /// Otherwise, return the next player
+    ///
+    /// # Arguments
+    ///
+    /// * `player_index` - The index of the current player
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rust_go_fish::GameState;
+    ///
+    /// let game = GameState::new(4);
+    /// let next_player_index = game.next_player_index(0);
+    /// assert_eq!(next_player_index, 1);
+    /// ```
+    pub fn next_player_index(&self, player_index: usize) -> usize {
+        (player_index + 1) % self.player_count
+    }
+
+    /// Get the index of the player who deals the first card and takes the first turn
+    pub fn first_player_index(&self) -> usize {
+        self.next_player_index(self.dealer_index)
+    }
+
+    /// Play a turn for a player
+    ///
+    /// # Panics
+    ///
+    /// If the turn breaks a rule, see `try_play_turn`
+    pub fn play_turn(&mut self, player_index: usize) -> PlayerTurnResult {
+        self.try_play_turn(player_index)
+            .unwrap_or_else(|violation| panic!("rule violation: {}", violation))
+    }
+
+    /// Play a turn for a player, or get the rule the turn broke
+    ///
+    /// In audit mode the card invariants are checked after the turn.
+    pub fn try_play_turn(
+        &mut self,
+        player_index: usize,
+    ) -> Result<PlayerTurnResult, RuleViolation> {
+        if self.verbose {
+            println!("player {} turn", player_index);
+        }
+        self.turns += 1;
+        if self.players[player_index].hand.is_empty() {
+            let result = self.play_empty_hand_turn(player_index);
+            return self.audited(result);
+        }
+        // get random card from player's hand and select another player to ask
+        let (random_card_index, other_player_index) = self.get_turn_indices(player_index);
+        let card = self.players[player_index].hand.cards()[random_card_index].clone();
+        let card = self.card_to_ask_for(player_index, card);
+        self.try_perform_ask(player_index, other_player_index, card)
+    }
+
+    /// Check the card invariants in audit mode before handing back the result of a turn
+    fn audited(&self, result: PlayerTurnResult) -> Result<PlayerTurnResult, RuleViolation> {
+        if self.audit {
+            check_invariants(self)?;
+        }
+        Ok(result)
+    }
+
+    /// Play the turn of a player without cards, who draws a card and asks with it
+    /// if the rules allow it and sits the turn out otherwise
+    fn play_empty_hand_turn(&mut self, player_index: usize) -> PlayerTurnResult {
+        if self.rules.empty_hand == EmptyHand::Draw {
+            if let Some(card) = self.deck.draw_first() {
+                if self.verbose {
+                    println!("player {} has no cards and draws from deck", player_index);
+                }
+                self.events.push(GameEvent::Drew {
+                    player_index,
+                    card: card.clone(),
+                });
+                self.players[player_index].add_card(card);
+                return PlayerTurnResult::PlayAgain;
+            }
+        }
+        if self.verbose {
+            println!("player {} sits out", player_index);
+        }
+        self.events.push(GameEvent::SatOut { player_index });
+        PlayerTurnResult::NextPlayerTurn
+    }
+
+    /// Get the players that can be asked for a card
+    ///
+    /// When the game goes on after a player runs out of cards, only the
+    /// players still holding cards are asked.
+    fn players_to_ask(&self, player_index: usize) -> Vec<usize> {
+        let others =
+            (1..self.player_count).map(|offset| (player_index + offset) % self.player_count);
+        let holding: Vec<usize> = others
+            .clone()
+            .filter(|&index| !self.players[index].hand.is_empty())
+            .collect();
+        if self.rules.empty_hand == EmptyHand::EndGame || holding.is_empty() {
+            others.collect()
+        } else {
+            holding
+        }
+    }
+
+    /// Get the indices of the card to play and the player to ask
+    /// based on the player's game mode selection
+    fn get_turn_indices(&mut self, player_index: usize) -> (usize, usize) {
+        match self.players[player_index].game_mode {
+            GameMode::Random => {
+                let askable = self.askable_card_indices(player_index);
+                let random_card_index = askable[get_random_from(&mut self.rng, 0..askable.len())];
+                let other_player_index = if self.rules.empty_hand == EmptyHand::EndGame {
+                    get_random_excluding_from(&mut self.rng, 0..self.player_count, player_index)
+                } else {
+                    let others = self.players_to_ask(player_index);
+                    others[get_random_from(&mut self.rng, 0..others.len())]
+                };
+                (random_card_index, other_player_index)
+            }
+            GameMode::Sequential => {
+                let random_card_index = self.askable_card_indices(player_index)[0];
+                let other_player_index = self.players_to_ask(player_index)[0];
+                (random_card_index, other_player_index)
+            }
+            GameMode::Search => {
+                let view = PlayerView::from_game(self, player_index);
+                let mut rng = StdRng::seed_from_u64(self.rng.gen());
+                let ask = choose_ask(&view, &SearchConfig::default(), &mut rng);
+                (ask.card_index, ask.other_player_index)
+            }
+        }
+    }
+
+    /// Perform a turn for a player
+    pub fn perform_turn(
+        &mut self,
+        player_index: usize,
+        other_player_index: usize,
+        card_index: usize,
+    ) -> PlayerTurnResult {
+        // get random card from player's hand
+        let card = self.players[player_index].hand.cards()[card_index].clone();
+        let card = self.card_to_ask_for(player_index, card);
+        self.perform_ask(player_index, other_player_index, card)
+    }
+
+    /// Perform a turn for a player asking for the given card, a rank in Go Fish
+    /// and an exact member in Happy Families
+    ///
+    /// # Panics
+    ///
+    /// If the ask breaks a rule, see `try_perform_ask`
+    pub fn perform_ask(
+        &mut self,
+        player_index: usize,
+        other_player_index: usize,
+        card: Card,
+    ) -> PlayerTurnResult {
+        self.try_perform_ask(player_index, other_player_index, card)
+            .unwrap_or_else(|violation| panic!("rule violation: {}", violation))
+    }
+
+    /// Perform a turn for a player asking for the given card, or get the rule the
+    /// ask broke
+    ///
+    /// An ask that is not legal is refused before any card moves. In audit mode
+    /// the card invariants are checked after the turn.
+    pub fn try_perform_ask(
+        &mut self,
+        player_index: usize,
+        other_player_index: usize,
+        card: Card,
+    ) -> Result<PlayerTurnResult, RuleViolation> {
+        check_ask(self, player_index, other_player_index, &card)?;
+        self.events.push(GameEvent::Asked {
+            asker: player_index,
+            asked: other_player_index,
+            card: card.clone(),
+        });
+        let asked_hand_size = self.players[other_player_index].hand.len();
+        let pairs_laid_down = self.players[player_index].pairs.len();
+        let go_fishes = self.players[player_index].go_fishes;
+        let result = self.resolve_ask(player_index, other_player_index, card.clone())?;
+
+        // record what every player saw of the ask
+        let player = &self.players[player_index];
+        self.asks.push(AskRecord {
+            turn: self.turns,
+            asker: player_index,
+            asked: other_player_index,
+            card,
+            received: asked_hand_size - self.players[other_player_index].hand.len(),
+            went_fishing: player.go_fishes != go_fishes,
+            laid_down: player.pairs.cards()[pairs_laid_down..].to_vec(),
+        });
+        self.audited(result)
+    }
+
+    /// Ask for a card and take it, or go fish
+    fn resolve_ask(
+        &mut self,
+        player_index: usize,
+        other_player_index: usize,
+        card: Card,
+    ) -> Result<PlayerTurnResult, RuleViolation> {
+        let wish = card.clone();
+
+        // ask the other player for the card
+        let result = match self.ask_for_card(player_index, other_player_index, card)? {
+            AskCardResult::ReceiveCard(card) => {
+                if self.verbose {
+                    let name = self.card_name(&card);
+                    println!("player {} received card: {}", player_index, name);
+                }
+                self.players[player_index].successful_asks += 1;
+                self.record_went_out(other_player_index);
+                self.match_player_pairs(player_index);
+                self.record_went_out(player_index);
+                PlayerTurnResult::PlayAgain
+            }
+            AskCardResult::GoFish => {
+                if self.verbose {
+                    println!("player {} go fish", player_index);
+                }
+                self.players[player_index].go_fishes += 1;
+                self.events.push(GameEvent::GoFish { player_index });
+                match self.deck.draw_first() {
+                    Some(card) => {
+                        if self.verbose {
+                            println!("player {} drew from deck", player_index);
+                        }
+                        let fished_wish = self.rules.fish_your_wish && self.is_wish(&card, &wish);
+                        self.events.push(GameEvent::Drew {
+                            player_index,
+                            card: card.clone(),
+                        });
+
+                        // add the card to the player's hand and lay down any pair it makes
+                        self.players[player_index].add_card(card);
+                        self.match_player_pairs(player_index);
+                        self.record_went_out(player_index);
+                        if fished_wish {
+                            if self.verbose {
+                                println!("player {} drew the card they asked for", player_index);
+                            }
+                            PlayerTurnResult::PlayAgain
+                        } else {
+                            PlayerTurnResult::NextPlayerTurn
+                        }
+                    }
+                    None => PlayerTurnResult::NextPlayerTurn,
+                }
+            }
+        };
+        Ok(result)
+    }
+
+    /// Check if a drawn card is the card a player asked for, the same rank in Go Fish
+    /// and the exact member in Happy Families
+    fn is_wish(&self, drawn: &Card, wish: &Card) -> bool {
+        if self.rules.families.is_some() {
+            drawn == wish
+        } else {
+            pairing_rank(drawn) == pairing_rank(wish)
+        }
+    }
+
+    /// Check if the game should end
+    ///
+    /// The game ends when the turn limit is reached. Under the end game rule for
+    /// empty hands it ends as soon as any player has no cards, otherwise it ends
+    /// once fewer than two players hold cards and nobody can draw any more.
+    pub fn check_win_condition(&self) -> EndGameCondition {
+        if let Some(turn_limit) = self.rules.turn_limit {
+            if self.turns >= turn_limit {
+                if self.verbose {
+                    println!("the turn limit of {} turns is reached", turn_limit);
+                }
+                return self.determine_winner();
+            }
+        }
+        if self.rules.empty_hand != EmptyHand::EndGame {
+            let holding = self
+                .players
+                .iter()
+                .filter(|player| !player.hand.is_empty())
+                .count();
+            let can_draw = self.rules.empty_hand == EmptyHand::Draw && !self.deck.is_empty();
+            if holding < 2 && !can_draw {
+                if self.verbose {
+                    println!("no more cards can be asked for");
+                }
+                return self.determine_winner();
+            }
+            return EndGameCondition::Continue;
+        }
+
+        // find any player with no cards in their hand
+        let empty_hands_index = self
+            .players
+            .iter()
+            .enumerate()
+            .find(|&(_, player)| player.hand.is_empty())
+            .map(|(index, _)| index);
+
+        // check to see if anyone no longer has cards in their hands
+        match empty_hands_index {
+            Some(index) => {
+                // determine a winner if any player has no more cards
+                if self.verbose {
+                    println!("player {} has no more cards", index);
+                }
+                self.determine_winner()
+            }
+            None => {
+                // continue play
+                EndGameCondition::Continue
+            }
+        }
+    }
+
+    /// Remember when a player runs out of cards for the first out tie breaker
+    pub(crate) fn record_went_out(&mut self, player_index: usize) {
+        if self.players[player_index].hand.is_empty() && !self.went_out.contains(&player_index) {
+            self.went_out.push(player_index);
+        }
+    }
+
+    /// Determine the winner of the game
+    pub fn determine_winner(&self) -> EndGameCondition {
+        self.determine_winner_with_tie_break().0
+    }
+
+    /// Get the tie breaker that decided the game, if one was needed
+    pub fn tie_break(&self) -> Option<TieBreaker> {
+        self.determine_winner_with_tie_break().1
+    }
+
+    /// Determine the winner of the game, applying the tie breakers from the rules
+    /// in order until a single player is left
+    fn determine_winner_with_tie_break(&self) -> (EndGameCondition, Option<TieBreaker>) {
+        let mut winners = self.indices_of_max_score_players();
+        if winners.len() > 1 {
+            for &tie_breaker in &self.rules.tie_breakers {
+                if tie_breaker == TieBreaker::SharedVictory {
+                    return (EndGameCondition::Tie(winners), Some(tie_breaker));
+                }
+                winners = self.apply_tie_breaker(tie_breaker, &winners);
+                if winners.len() == 1 {
+                    return (EndGameCondition::Winner(winners[0]), Some(tie_breaker));
+                }
+            }
+        }
+
+        let outcome = match winners.len() {
+            0 => EndGameCondition::Continue,
+            1 => EndGameCondition::Winner(winners[0]),
+            _ => EndGameCondition::Tie(winners),
+        };
+        (outcome, None)
+    }
+
+    /// Narrow down the tied players to the ones favoured by the tie breaker
+    fn apply_tie_breaker(&self, tie_breaker: TieBreaker, tied: &[usize]) -> Vec<usize> {
+        match tie_breaker {
+            TieBreaker::FewestCardsInHand => {
+                let fewest = tied
+                    .iter()
+                    .map(|&index| self.players[index].hand.len())
+                    .min()
+                    .unwrap_or(0);
+                tied.iter()
+                    .copied()
+                    .filter(|&index| self.players[index].hand.len() == fewest)
+                    .collect()
+            }
+            TieBreaker::HighestRankedPair => {
+                let highest_pair = |index: usize| {
+                    self.players[index]
+                        .pairs
+                        .cards()
+                        .iter()
+                        .filter(|card| !self.is_wild(card))
+                        .map(|card| card.rank.weight)
+                        .max()
+                };
+                let highest = tied
+                    .iter()
+                    .map(|&index| highest_pair(index))
+                    .max()
+                    .flatten();
+                tied.iter()
+                    .copied()
+                    .filter(|&index| highest_pair(index) == highest)
+                    .collect()
+            }
+            TieBreaker::FirstOut => {
+                match self.went_out.iter().find(|&index| tied.contains(index)) {
+                    Some(&index) => vec![index],
+                    None => tied.to_vec(),
+                }
+            }
+            TieBreaker::SharedVictory => tied.to_vec(),
+        }
+    }
+
+    /// Get the indices of the players with the highest score
+    fn indices_of_max_score_players(&self) -> Vec<usize> {
+        if self.players.is_empty() {
+            return Vec::new();
+        }
+
+        let max_score = self
+            .players
+            .iter()
+            .map(|player| player.score)
+            .max()
+            .unwrap(); // Safe to use unwrap as players is not empty
+
+        self.players
+            .iter()
+            .enumerate()
+            .filter_map(|(index, player)| {
+                if player.score == max_score {
+                    Some(index)
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+
+    /// Transfer a card from one player to another, refusing a card the other
+    /// player does not hold
+    fn transfer_cards(
+        &mut self,
+        player_index: usize,
+        other_player_index: usize,
+        card: Card,
+    ) -> Result<(), RuleViolation> {
+        match self.players[other_player_index].hand.remove_card(&card) {
+            Some(card) => {
+                self.players[player_index].hand.push(card);
+                Ok(())
+            }
+            None => Err(RuleViolation::CardNotHeld {
+                player_index: other_player_index,
+                card,
+            }),
+        }
+    }
+
+    /// Check that a player's answer to an ask matches their hand: a card they
+    /// hold that was asked for, or a wild card they must surrender, or nothing
+    /// when they hold no card that could be asked for
+    fn check_answer(
+        &self,
+        answering_player_index: usize,
+        card: &Card,
+        answer: Option<&Card>,
+    ) -> Result<(), RuleViolation> {
+        let hand = &self.players[answering_player_index].hand;
+        let surrenders_wilds = self
+            .rules
+            .wild
+            .as_ref()
+            .is_some_and(|wild| wild.surrender_when_asked && self.rules.families.is_none());
+        let answers = |held: &Card| {
+            if self.rules.families.is_some() {
+                held == card
+            } else {
+                pairing_rank(held) == pairing_rank(card) || (surrenders_wilds && self.is_wild(held))
+            }
+        };
+        let honest = match answer {
+            Some(answer) => hand.contains(answer) && answers(answer),
+            None => !hand.cards().iter().any(answers),
+        };
+        if honest {
+            Ok(())
+        } else {
+            Err(RuleViolation::FalseAnswer {
+                player_index: answering_player_index,
+                card: card.clone(),
+            })
+        }
+    }
+
+    /// Check if a card is wild under the rules of the game
+    fn is_wild(&self, card: &Card) -> bool {
+        self.rules
+            .wild
+            .as_ref()
+            .is_some_and(|wild| wild.cards.is_wild(card))
+    }
+
+    /// Ask another player for a card
+    /// If they have it, take it, and every other card of the rank if the rules
+    /// make players surrender all of them
+    /// If they don't, GoFish
+    /// If the rules make wild cards be surrendered when asked, a player
+    /// without the asked rank hands over a wild card instead
+    /// In Happy Families only the exact card asked for is handed over
+    /// The answer is checked against the answering player's hand, so a false
+    /// answer or a card that is not held is refused before any card moves
+    pub fn ask_for_card(
+        &mut self,
+        player_index: usize,
+        answering_player_index: usize,
+        card: Card,
+    ) -> Result<AskCardResult, RuleViolation> {
+        let answering_player = &self.players[answering_player_index];
+        let result = if self.rules.families.is_some() {
+            answering_player.answer_for_card(&card)
+        } else {
+            answering_player
+                .answer_for_card_rank(card.rank)
+                .or_else(|| match &self.rules.wild {
+                    Some(wild) if wild.surrender_when_asked => answering_player
+                        .hand
+                        .cards()
+                        .iter()
+                        .find(|card| wild.cards.is_wild(card))
+                        .cloned(),
+                    _ => None,
+                })
+        };
+        self.check_answer(answering_player_index, &card, result.as_ref())?;
+        // check if they have the card
+        match result {
+            Some(card) => {
+                // take the card
+                self.transfer_cards(player_index, answering_player_index, card.clone())?;
+                let mut cards = vec![card.clone()];
+                if self.rules.surrender_all && self.rules.families.is_none() {
+                    while let Some(other) =
+                        self.players[answering_player_index].answer_for_card_rank(card.rank)
+                    {
+                        self.transfer_cards(player_index, answering_player_index, other.clone())?;
+                        cards.push(other);
+                    }
+                }
+                self.events.push(GameEvent::HandedOver {
+                    from: answering_player_index,
+                    to: player_index,
+                    cards,
+                });
+                Ok(AskCardResult::ReceiveCard(card))
+            }
+            None => Ok(AskCardResult::GoFish),
+        }
+    }
+}
+
+/// Run the game
+pub fn run_game(game: &mut GameState) -> Standings {
+    let first_player_index = game.first_player_index();
+    run_game_from(game, first_player_index)
+}
+
+/// Run the rest of a game in progress, starting with the turn of the given player
+///
+/// # Panics
+///
+/// If a turn breaks a rule, see `try_run_game_from`
+pub fn run_game_from(game: &mut GameState, player_index: usize) -> Standings {
+    try_run_game_from(game, player_index)
+        .unwrap_or_else(|violation| panic!("rule violation: {}", violation))
+}
+
+/// Run the game, or get the first rule a turn broke
+pub fn try_run_game(game: &mut GameState) -> Result<Standings, RuleViolation> {
+    let first_player_index = game.first_player_index();
+    try_run_game_from(game, first_player_index)
+}
+
+/// Run the rest of a game in progress, starting with the turn of the given
+/// player, or get the first rule a turn broke
+pub fn try_run_game_from(
+    game: &mut GameState,
+    player_index: usize,
+) -> Result<Standings, RuleViolation> {
+    try_run_game_observed(game, player_index, |_| {})
+}
+
+/// Run the rest of a game in progress like `try_run_game_from`, handing the
+/// game to an observer after every turn, such as a spectator following the table
+pub fn try_run_game_observed<F>(
+    game: &mut GameState,
+    player_index: usize,
+    mut observer: F,
+) -> Result<Standings, RuleViolation>
+where
+    F: FnMut(&GameState),
+{
+    let mut current_player_index = player_index;
+    let mut end_game_condition = EndGameCondition::Continue;
+
+    // Game Loop
+    while end_game_condition == EndGameCondition::Continue {
+        let mut turn_result = PlayerTurnResult::PlayAgain;
+        while turn_result == PlayerTurnResult::PlayAgain {
+            // Update the outer 'turn_result' variable with the new turn result
+            turn_result = game.try_play_turn(current_player_index)?;
+            observer(game);
+
+            // Check the end game condition after each turn
+            end_game_condition = game.check_win_condition();
+            if end_game_condition != EndGameCondition::Continue {
+                break; // Break from the inner loop if the game should end
+            }
+        }
+        current_player_index = game.next_player_index(current_player_index);
+    }
+
+    let tie_break = game.tie_break();
+    Ok(Standings::from_game(end_game_condition, tie_break, game))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::deck::{is_joker, DeckConfig};
+    use crate::families::{Families, FAMILY_SIZE};
+    use crate::rules::DEFAULT_SEQUENTIAL_TURN_LIMIT;
+    use cardpack::{JACK, TWO};
+
+    fn setup_random() -> GameState {
+        let mut game = GameState::new(4);
+        game.deal();
+        game.players_match_cards();
+        game
+    }
+
+    fn setup() -> GameState {
+        let standard52 = Standard52::default();
+        let mut game = GameState::new_with_deck(4, standard52, GameMode::Sequential);
+        game.deal();
+        game.players_match_cards();
+        game
+    }
+
+    #[test]
+    fn test_find_pair() {
+        let index_string = "2S 2D QS KH 3C 3S";
+        let pile = Standard52::pile_from_index(index_string).unwrap();
+        let result = find_pair(&pile).unwrap();
+
+        assert_eq!(
+            result,
+            (
+                Standard52::card_from_index("2S"),
+                Standard52::card_from_index("2D")
+            )
+        );
+    }
+
+    #[test]
+    fn test_find_pair_identical_cards() {
+        let mut pile = Standard52::pile_from_index("2S QS 2S").unwrap();
+        let result = find_pair(&pile).unwrap();
+        let two_of_spades = Standard52::card_from_index("2S");
+        assert_eq!(result, (two_of_spades.clone(), two_of_spades.clone()));
+
+        pile.remove_card(&result.0);
+        pile.remove_card(&result.1);
+        assert_eq!(pile.to_index(), "QS");
+    }
+
+    #[test]
+    fn test_find_pair_jokers() {
+        let mut pile = Standard52::pile_from_index("QS").unwrap();
+        pile.append(&Pile::jokers());
+        let result = find_pair(&pile).unwrap();
+        assert_eq!(
+            result,
+            (
+                Pile::jokers().cards()[0].clone(),
+                Pile::jokers().cards()[1].clone()
+            )
+        );
+    }
+
+    #[test]
+    fn test_events_record_the_turn() {
+        let mut game = setup();
+        game.verbose = false;
+        game.events.clear();
+        game.players[0].hand = Standard52::pile_from_index("5C 9S").unwrap().into();
+        game.players[1].hand = Standard52::pile_from_index("5H KD").unwrap().into();
+        game.players[2].hand = Standard52::pile_from_index("QS").unwrap().into();
+        game.deck = Standard52::pile_from_index("2H 3H").unwrap();
+
+        game.perform_turn(0, 1, 0);
+        game.perform_turn(0, 2, 0);
+        assert_eq!(
+            game.transcript(),
+            "player 0 asks player 1 for 5C\n\
+             player 1 hands player 0 5H\n\
+             player 0 lays down 5H 5C\n\
+             player 0 asks player 2 for 9S\n\
+             player 0 goes fish\n\
+             player 0 draws 2H\n"
+        );
+    }
+
+    #[test]
+    fn test_ask_for_joker_takes_the_other_joker() {
+        let mut game = setup();
+        let big_joker = Pile::jokers().cards()[0].clone();
+        let little_joker = Pile::jokers().cards()[1].clone();
+        game.players[0].hand = Pile::from_vector(vec![big_joker.clone()]).into();
+        game.players[1].hand = Pile::from_vector(vec![little_joker.clone()]).into();
+
+        assert_eq!(
+            game.ask_for_card(0, 1, big_joker),
+            Ok(AskCardResult::ReceiveCard(little_joker))
+        );
+    }
+
+    fn twos_wild() -> WildRules {
+        WildRules::new(WildCards::Rank(Rank::new(TWO)))
+    }
+
+    #[test]
+    fn test_find_pair_with_wilds_prefers_natural_pair() {
+        let pile = Standard52::pile_from_index("2S KH 3C 3S").unwrap();
+        let result = find_pair_with_wilds(&pile, &twos_wild().cards).unwrap();
+        assert_eq!(
+            result,
+            (
+                Standard52::card_from_index("3C"),
+                Standard52::card_from_index("3S")
+            )
+        );
+    }
+
+    #[test]
+    fn test_find_pair_with_wilds_pairs_highest_single() {
+        let pile = Standard52::pile_from_index("2S 5H KH 3C").unwrap();
+        let result = find_pair_with_wilds(&pile, &twos_wild().cards).unwrap();
+        assert_eq!(
+            result,
+            (
+                Standard52::card_from_index("KH"),
+                Standard52::card_from_index("2S")
+            )
+        );
+    }
+
+    #[test]
+    fn test_find_pair_with_wild_jokers() {
+        let mut pile = Standard52::pile_from_index("QS").unwrap();
+        pile.append(&Pile::jokers());
+        let mut player = Player::new();
+        player.add_cards(pile);
+        let wild = WildRules {
+            wild_pair_score: 2,
+            ..WildRules::new(WildCards::Jokers)
+        };
+        let result = player.match_pairs_with_wilds(&wild);
+
+        assert_eq!(result, 1);
+        assert_eq!(player.score, 2);
+        assert_eq!(player.hand.len(), 1);
+        assert!(is_joker(&player.hand.cards()[0]));
+    }
+
+    #[test]
+    fn test_player_match_pairs_with_wilds_scores_natural_pairs() {
+        let pile = Standard52::pile_from_index("2S 2D QS QH 3C").unwrap();
+        let mut player = Player::new();
+        player.add_cards(pile);
+        let wild = WildRules {
+            wild_pair_score: 0,
+            ..twos_wild()
+        };
+        let result = player.match_pairs_with_wilds(&wild);
+
+        // the queens pair naturally, the three takes a two and the last two is left over
+        assert_eq!(result, 2);
+        assert_eq!(player.score, 1);
+        assert_eq!(player.hand.len(), 1);
+    }
+
+    #[test]
+    fn test_ask_for_card_surrenders_wild() {
+        let mut game = setup();
+        game.players[0].hand = Standard52::pile_from_index("5C").unwrap().into();
+        game.players[1].hand = Standard52::pile_from_index("KS 2H").unwrap().into();
+        let card = Standard52::card_from_index("5C");
+
+        game.rules.wild = Some(twos_wild());
+        assert_eq!(
+            game.ask_for_card(0, 1, card.clone()),
+            Ok(AskCardResult::GoFish)
+        );
+
+        game.rules.wild = Some(WildRules {
+            surrender_when_asked: true,
+            ..twos_wild()
+        });
+        let result = game.ask_for_card(0, 1, card);
+        assert_eq!(
+            result,
+            Ok(AskCardResult::ReceiveCard(Standard52::card_from_index(
+                "2H"
+            )))
+        );
+        assert_eq!(game.players[1].hand.to_index(), "KS");
+    }
+
+    #[test]
+    fn test_strategies_do_not_ask_for_wilds() {
+        let mut game = setup();
+        game.rules.wild = Some(twos_wild());
+        game.players[0].hand = Standard52::pile_from_index("2S 9C").unwrap().into();
+
+        let (card_index, _) = game.get_turn_indices(0);
+        assert_eq!(card_index, 1);
+
+        game.players[0].game_mode = GameMode::Random;
+        for _ in 0..10 {
+            let (card_index, _) = game.get_turn_indices(0);
+            assert_eq!(card_index, 1);
+        }
+    }
+
+    #[test]
+    fn test_run_game_with_wild_jokers() {
+        let rules = Rules {
+            deck: DeckConfig {
+                jokers: true,
+                ..DeckConfig::default()
+            },
+            wild: Some(WildRules::new(WildCards::Jokers)),
+            ..Rules::default()
+        };
+        let mut game =
+            GameState::new_with_rules(vec![GameMode::Random; 4], rules, StdRng::seed_from_u64(9));
+        game.verbose = false;
+        game.deal();
+        game.players_match_cards();
+        let standings = run_game(&mut game);
+
+        assert_ne!(standings.outcome, EndGameCondition::Continue);
+        let pairs: usize = game.players.iter().map(|player| player.pairs.len()).sum();
+        assert_eq!(pairs % 2, 0);
+    }
+
+    fn setup_happy_families() -> GameState {
+        let families = Families::classic(6);
+        let rules = Rules {
+            deck: families.deck_config(),
+            families: Some(families),
+            book_size: FAMILY_SIZE,
+            ..Rules::default()
+        };
+        let mut game =
+            GameState::new_with_rules(vec![GameMode::Random; 3], rules, StdRng::seed_from_u64(4));
+        game.verbose = false;
+        game
+    }
+
+    #[test]
+    fn test_find_book() {
+        let pile = Standard52::pile_from_index("2S KH 2D 3C 2H KS 2C").unwrap();
+        assert_eq!(find_book(&pile, 4).unwrap().len(), 4);
+        assert_eq!(find_book(&pile, 2).unwrap().len(), 2);
+        assert_eq!(find_book(&pile, 5), None);
+    }
+
+    #[test]
+    fn test_player_match_books() {
+        let pile = Standard52::pile_from_index("2S KH 2D 3C 2H KS 2C").unwrap();
+        let mut player = Player::new();
+        player.add_cards(pile);
+        let result = player.match_books(FAMILY_SIZE);
+
+        assert_eq!(result, 1);
+        assert_eq!(player.score, 1);
+        assert_eq!(player.pairs.len(), 4);
+        assert_eq!(player.hand.len(), 3);
+    }
+
+    #[test]
+    fn test_happy_families_ask_for_exact_member() {
+        let mut game = setup_happy_families();
+        game.players[0].game_mode = GameMode::Sequential;
+        game.players[0].hand = Standard52::pile_from_index("AS AD").unwrap().into();
+        game.players[1].hand = Standard52::pile_from_index("AC").unwrap().into();
+        game.deck = Standard52::pile_from_index("KS").unwrap();
+
+        // the first missing member of the aces is the ace of hearts, which nobody holds
+        let result = game.perform_turn(0, 1, 0);
+        assert_eq!(result, PlayerTurnResult::NextPlayerTurn);
+        assert_eq!(game.players[1].hand.to_index(), "AC");
+
+        let card = Standard52::card_from_index("AC");
+        assert_eq!(
+            game.ask_for_card(0, 1, card.clone()),
+            Ok(AskCardResult::ReceiveCard(card))
+        );
+    }
+
+    #[test]
+    fn test_happy_families_lays_down_complete_family() {
+        let mut game = setup_happy_families();
+        game.players[0].game_mode = GameMode::Sequential;
+        game.players[0].hand = Standard52::pile_from_index("AS AH AD 9C").unwrap().into();
+        game.players[1].hand = Standard52::pile_from_index("AC KS").unwrap().into();
+
+        let result = game.perform_turn(0, 1, 0);
+
+        assert_eq!(result, PlayerTurnResult::PlayAgain);
+        assert_eq!(game.players[0].score, 1);
+        assert_eq!(game.players[0].hand.to_index(), "9C");
+    }
+
+    #[test]
+    fn test_run_game_happy_families() {
+        let mut game = setup_happy_families();
+        game.deal();
+        game.players_match_cards();
+        let standings = run_game(&mut game);
+
+        assert_ne!(standings.outcome, EndGameCondition::Continue);
+        assert!(game
+            .players
+            .iter()
+            .all(|player| player.pairs.len() == player.score as usize * FAMILY_SIZE));
+        assert_eq!(
+            standings
+                .players
+                .iter()
+                .map(|p| p.pairs_made)
+                .sum::<usize>(),
+            game.players.iter().map(|p| p.score as usize).sum::<usize>()
+        );
+    }
+
+    #[test]
+    fn test_deal_uses_hand_size_table() {
+        let rules = Rules::preset("books").unwrap();
+        for (player_count, hand_size) in [(3, 7), (5, 5)] {
+            let modes = vec![GameMode::Random; player_count];
+            let mut game =
+                GameState::new_with_rules(modes, rules.clone(), StdRng::seed_from_u64(1));
+            game.deal();
+            let dealt: usize = game
+                .players
+                .iter()
+                .map(|player| player.hand.len() + player.pairs.len())
+                .sum();
+            assert_eq!(dealt, player_count * hand_size);
+        }
+    }
+
+    #[test]
+    fn test_books_of_four_and_surrender_all() {
+        let mut game = setup();
+        game.rules.book_size = 4;
+        game.rules.surrender_all = true;
+        game.players[0].hand = Standard52::pile_from_index("5C 5D 9S").unwrap().into();
+        game.players[1].hand = Standard52::pile_from_index("5H KS 5S").unwrap().into();
+
+        let result = game.perform_turn(0, 1, 0);
+
+        assert_eq!(result, PlayerTurnResult::PlayAgain);
+        assert_eq!(game.players[0].score, 1);
+        assert_eq!(game.players[0].hand.to_index(), "9S");
+        assert_eq!(game.players[1].hand.to_index(), "KS");
+    }
+
+    #[test]
+    fn test_fish_your_wish_plays_again() {
+        let mut game = setup();
+        game.players[0].hand = Standard52::pile_from_index("5C 9S").unwrap().into();
+        game.players[1].hand = Standard52::pile_from_index("KS").unwrap().into();
+        game.deck = Standard52::pile_from_index("5D 5H").unwrap();
+
+        assert_eq!(game.perform_turn(0, 1, 0), PlayerTurnResult::NextPlayerTurn);
+
+        game.players[0].hand = Standard52::pile_from_index("5C 9S").unwrap().into();
+        game.rules.fish_your_wish = true;
+        assert_eq!(game.perform_turn(0, 1, 0), PlayerTurnResult::PlayAgain);
+        assert_eq!(game.players[0].hand.to_index(), "9S");
+    }
+
+    #[test]
+    fn test_empty_hand_draw_and_sit_out() {
+        let mut game = setup();
+        game.verbose = false;
+        game.players[0].hand = Hand::new();
+        assert_ne!(game.check_win_condition(), EndGameCondition::Continue);
+
+        game.rules.empty_hand = EmptyHand::Draw;
+        assert_eq!(game.check_win_condition(), EndGameCondition::Continue);
+        assert_eq!(game.play_turn(0), PlayerTurnResult::PlayAgain);
+        assert_eq!(game.players[0].hand.len(), 1);
+
+        game.rules.empty_hand = EmptyHand::SitOut;
+        game.players[0].hand = Hand::new();
+        assert_eq!(game.play_turn(0), PlayerTurnResult::NextPlayerTurn);
+        assert!(!game.players_to_ask(1).contains(&0));
+        for index in 1..3 {
+            game.players[index].hand = Hand::new();
+        }
+        assert_ne!(game.check_win_condition(), EndGameCondition::Continue);
+    }
+
+    #[test]
+    fn test_turn_limit_ends_game() {
+        let mut game = setup();
+        game.verbose = false;
+        game.rules.turn_limit = Some(3);
+        let standings = run_game(&mut game);
+
+        assert_ne!(standings.outcome, EndGameCondition::Continue);
+        assert!(game.turns <= 3);
+    }
+
+    #[test]
+    fn test_sequential_players_get_a_turn_limit() {
+        let game = GameState::new_with_deck(3, Standard52::default(), GameMode::Sequential);
+        assert_eq!(game.rules.turn_limit, Some(DEFAULT_SEQUENTIAL_TURN_LIMIT));
+        let game = GameState::new_with_deck(3, Standard52::default(), GameMode::Random);
+        assert_eq!(game.rules.turn_limit, None);
+
+        let modes = vec![GameMode::Random, GameMode::Sequential];
+        let game =
+            GameState::new_with_rules(modes.clone(), Rules::default(), StdRng::seed_from_u64(1));
+        assert_eq!(game.rules.turn_limit, Some(DEFAULT_SEQUENTIAL_TURN_LIMIT));
+        let rules = Rules {
+            turn_limit: Some(20),
+            ..Rules::default()
+        };
+        let mut game = GameState::new_with_rules(modes, rules, StdRng::seed_from_u64(1));
+        assert_eq!(game.rules.turn_limit, Some(20));
+        game.verbose = false;
+        game.deal();
+        game.players_match_cards();
+        run_game(&mut game);
+        assert!(game.turns <= 20);
+    }
+
+    #[test]
+    fn test_run_game_presets() {
+        for name in Rules::preset_names() {
+            let rules = Rules::preset(name).unwrap();
+            let modes = vec![rules.game_mode; 4];
+            let mut game = GameState::new_with_rules(modes, rules, StdRng::seed_from_u64(2));
+            game.verbose = false;
+            game.deal();
+            game.players_match_cards();
+            let standings = run_game(&mut game);
+            assert_ne!(standings.outcome, EndGameCondition::Continue, "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_run_game_presets_in_audit_mode() {
+        for name in Rules::preset_names() {
+            let rules = Rules::preset(name).unwrap();
+            let modes = vec![GameMode::Random; 3];
+            let mut game = GameState::new_with_rules(modes, rules, StdRng::seed_from_u64(4));
+            game.verbose = false;
+            game.audit = true;
+            game.deal();
+            game.players_match_cards();
+            assert!(try_run_game(&mut game).is_ok(), "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_try_perform_ask_refuses_illegal_ask() {
+        let mut game = setup();
+        game.players[0].hand = Standard52::pile_from_index("5C 9S").unwrap().into();
+        game.players[1].hand = Standard52::pile_from_index("KH").unwrap().into();
+        let king = Standard52::card_from_index("KS");
+
+        assert_eq!(
+            game.try_perform_ask(0, 1, king.clone()),
+            Err(RuleViolation::RankNotHeld {
+                player_index: 0,
+                card: king.clone()
+            })
+        );
+        assert_eq!(game.players[1].hand.to_index(), "KH");
+        assert!(game.asks.is_empty());
+
+        assert_eq!(
+            game.transfer_cards(1, 0, king.clone()),
+            Err(RuleViolation::CardNotHeld {
+                player_index: 0,
+                card: king
+            })
+        );
+    }
+
+    #[test]
+    fn test_audit_mode_reports_broken_invariant() {
+        let mut game = GameState::new_seeded(vec![GameMode::Random; 3], 9);
+        game.verbose = false;
+        game.deal();
+        game.players_match_cards();
+        game.audit = true;
+        assert!(game.try_play_turn(0).is_ok());
+
+        let card = game.deck.draw_first().unwrap();
+        assert_eq!(game.try_play_turn(1), Err(RuleViolation::MissingCard(card)));
+    }
+
+    #[test]
+    fn test_player_match_pairs_two_decks() {
+        let pile = Standard52::pile_from_index("2S 2S 2S 2S 2D QS QS 3C").unwrap();
+        let mut player = Player::new();
+        player.add_cards(pile);
+        let result = player.match_pairs();
+        assert_eq!(result, 3);
+        assert_eq!(player.score, 3);
+        assert_eq!(player.hand.len(), 2);
+        assert_eq!(player.hand.ranks().len(), 2);
+    }
+
+    #[test]
+    fn test_deal_two_decks_for_eight_players() {
+        let rules = Rules {
+            deck: DeckConfig::for_players(8),
+            ..Rules::default()
+        };
+        let mut game =
+            GameState::new_with_rules(vec![GameMode::Random; 8], rules, StdRng::seed_from_u64(3));
+        game.deal();
+
+        assert!(game.players.iter().all(|player| player.hand.len() == 7));
+        assert_eq!(game.deck.len(), 104 - 8 * 7);
+    }
+
+    #[test]
+    fn test_deal_stops_when_deck_runs_out() {
+        let mut game = setup();
+        game.deck = Standard52::pile_from_index("2S 3S 4S").unwrap();
+        game.players
+            .iter_mut()
+            .for_each(|player| player.hand = Hand::new());
+        game.deal();
+
+        assert!(game.deck.is_empty());
+        let hand_sizes: Vec<usize> = game
+            .players
+            .iter()
+            .map(|player| player.hand.len())
+            .collect();
+        assert_eq!(hand_sizes, vec![1, 1, 1, 0]);
+    }
+
+    #[test]
+    fn test_player_answer_for_card_rank_in_hand() {
+        let index_string = "2S 2D QS KH 3C 3S";
+        let pile = Standard52::pile_from_index(index_string).unwrap();
+        let mut player = Player::new();
+        player.add_cards(pile);
+        let result = player.answer_for_card_rank(Rank::new(TWO)).unwrap();
+
+        assert_eq!(result, Standard52::card_from_index("2S"));
+    }
+
+    #[test]
+    fn test_player_answer_for_card_rank_not_in_hand() {
+        let index_string = "2S 2D QS KH 3C 3S";
+        let pile = Standard52::pile_from_index(index_string).unwrap();
+        let mut player = Player::new();
+        player.add_cards(pile);
+        let result = player.answer_for_card_rank(Rank::new(JACK));
+        assert_eq!(result, None);
+    }
+
+    #[test]
+    fn test_player_match_pairs() {
+        let index_string = "2S 2D QS KH 3C 3S";
+        let pile = Standard52::pile_from_index(index_string).unwrap();
+        let mut player = Player::new();
+        player.add_cards(pile);
+        let result = player.match_pairs();
+        assert_eq!(result, 2);
+        assert_eq!(player.hand.len(), 2);
+        assert_eq!(player.pairs.len(), 4);
+    }
+
+    #[test]
+    fn test_setup_game() {
+        let game = setup();
+        assert_eq!(game.players.len(), 4);
+        assert_eq!(game.players[0].hand.len(), 7);
+
+        assert_eq!(game.players[0].hand.to_index_str(), "AS JH TS 7H 6S 3H 2S");
+        assert_eq!(game.players[1].hand.to_index_str(), "AH KS TH 9S 6H 5S 2H");
+        assert_eq!(game.players[2].hand.to_index_str(), "AD KH QS 9H 8S 5H 4S");
+        assert_eq!(game.players[3].hand.to_index_str(), "KD QH JS 8H 7S 4H 3S");
+    }
+
+    #[test]
+    fn test_setup_game_rotated_dealer() {
+        let mut game = GameState::new_with_deck(4, Standard52::default(), GameMode::Sequential);
+        game.dealer_index = 0;
+        game.deal();
+        game.players_match_cards();
+
+        assert_eq!(game.first_player_index(), 1);
+        assert_eq!(game.players[1].hand.to_index_str(), "AS JH TS 7H 6S 3H 2S");
+        assert_eq!(game.players[0].hand.to_index_str(), "KD QH JS 8H 7S 4H 3S");
+    }
+
+    #[test]
+    fn test_go_fish_draw_lays_down_pair() {
+        let mut game = setup();
+        game.players[0].hand = Standard52::pile_from_index("5C").unwrap().into();
+        game.players[1].hand = Standard52::pile_from_index("KS").unwrap().into();
+        game.deck = Standard52::pile_from_index("5D 9C").unwrap();
+
+        let result = game.perform_turn(0, 1, 0);
+
+        assert_eq!(result, PlayerTurnResult::NextPlayerTurn);
+        assert_eq!(game.players[0].go_fishes, 1);
+        assert_eq!(game.players[0].pairs.len(), 2);
+        assert!(game.players[0].hand.is_empty());
+        assert_eq!(game.went_out, vec![0]);
+    }
+
+    #[test]
+    fn test_run_game_seeded_is_repeatable() {
+        let modes = vec![GameMode::Random, GameMode::Sequential, GameMode::Random];
+        let mut first = GameState::new_seeded(modes.clone(), 11);
+        let mut second = GameState::new_seeded(modes, 11);
+        for game in [&mut first, &mut second] {
+            game.verbose = false;
+            game.deal();
+            game.players_match_cards();
+        }
+
+        assert_eq!(run_game(&mut first), run_game(&mut second));
+    }
+
+    #[test]
+    fn test_player_ask_for_card() {
+        let mut game = setup();
+        let card = Standard52::card_from_index("6S");
+        let current_player_index = 0;
+        let answering_player_index = 1;
+        let _player_turn_result =
+            game.ask_for_card(current_player_index, answering_player_index, card);
+        assert_eq!(game.players[current_player_index].hand.len(), 8);
+        game.players[current_player_index].match_pairs();
+        assert_eq!(game.players[current_player_index].pairs.len(), 2);
+    }
+
+    #[test]
+    fn test_game_play_turn() {
+        let mut game = setup();
+        let current_player_index = 0;
+        let _player_turn_result = game.play_turn(current_player_index);
+
+        assert_eq!(game.players[current_player_index].hand.len(), 6);
+        game.players[current_player_index].match_pairs();
+        assert_eq!(game.players[current_player_index].pairs.len(), 2);
+    }
+
+    #[test]
+    fn test_game_play_turn_counts_asks() {
+        let mut game = setup();
+        let _player_turn_result = game.play_turn(0);
+        assert_eq!(game.players[0].successful_asks, 1);
+        assert_eq!(game.players[0].go_fishes, 0);
+    }
+
+    #[test]
+    fn test_run_game() {
+        let mut game = setup();
+        let result = run_game(&mut game);
+        match result.outcome {
+            EndGameCondition::Winner(winner_index) => {
+                assert_eq!(winner_index, 2);
+                assert_eq!(game.players[winner_index].score, 8);
+                assert_eq!(
+                    game.players[winner_index].pairs.to_index_str(),
+                    "AD AC QS QH 8S 8H 5H 5D 4S 4H 2D 2C JS JC 3S 3C"
+                );
+            }
+            EndGameCondition::Tie(_tie_indices) => {}
+            EndGameCondition::Continue => {}
+        }
+    }
+
+    #[test]
+    fn test_run_game_random() {
+        let mut game = setup_random();
+        let result = run_game(&mut game);
+        match &result.outcome {
+            EndGameCondition::Winner(winner_index) => {
+                println!("winner_index: {:?}", winner_index);
+            }
+            EndGameCondition::Tie(_tie_indices) => {}
+            EndGameCondition::Continue => {}
+        }
+        assert_ne!(result.outcome, EndGameCondition::Continue);
+    }
+
+    fn setup_tie() -> GameState {
+        let mut game = setup();
+        game.players[0].score = 2;
+        game.players[0].pairs = Standard52::pile_from_index("3S 3D 4S 4D").unwrap();
+        game.players[1].score = 2;
+        game.players[1].pairs = Standard52::pile_from_index("KS KD 2S 2D").unwrap();
+        game.players[1].hand = Standard52::pile_from_index("5C").unwrap().into();
+        game.players[2].score = 1;
+        game
+    }
+
+    #[test]
+    fn test_determine_winner_tie_without_tie_breakers() {
+        let game = setup_tie();
+        assert_eq!(game.determine_winner(), EndGameCondition::Tie(vec![0, 1]));
+        assert_eq!(game.tie_break(), None);
+    }
+
+    #[test]
+    fn test_determine_winner_fewest_cards_in_hand() {
+        let mut game = setup_tie();
+        game.rules.tie_breakers = vec![TieBreaker::FewestCardsInHand];
+        assert_eq!(game.determine_winner(), EndGameCondition::Winner(1));
+        assert_eq!(game.tie_break(), Some(TieBreaker::FewestCardsInHand));
+    }
+
+    #[test]
+    fn test_determine_winner_highest_ranked_pair() {
+        let mut game = setup_tie();
+        game.rules.tie_breakers = vec![TieBreaker::HighestRankedPair];
+        assert_eq!(game.determine_winner(), EndGameCondition::Winner(1));
+    }
+
+    #[test]
+    fn test_determine_winner_first_out_falls_through() {
+        let mut game = setup_tie();
+        game.went_out = vec![2, 0];
+        game.rules.tie_breakers = vec![TieBreaker::SharedVictory, TieBreaker::FirstOut];
+        assert_eq!(game.determine_winner(), EndGameCondition::Tie(vec![0, 1]));
+        assert_eq!(game.tie_break(), Some(TieBreaker::SharedVictory));
+
+        game.rules.tie_breakers = vec![TieBreaker::FirstOut];
+        assert_eq!(game.determine_winner(), EndGameCondition::Winner(0));
+        assert_eq!(game.tie_break(), Some(TieBreaker::FirstOut));
+    }
+
+    #[test]
+    fn test_game_next_player_index() {
+        let game = setup();
+        let next_player_index = game.next_player_index(0);
+        assert_eq!(next_player_index, 1);
+    }
+}
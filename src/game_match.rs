@@ -0,0 +1,317 @@
+use crate::game::{run_game, EndGameCondition, GameMode, GameState};
+use crate::nth_seed;
+use crate::rules::Rules;
+use crate::standings::Standings;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use serde::Serialize;
+use std::fmt;
+
+/// How long a match lasts
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Serialize)]
+pub enum MatchLength {
+    /// Play a fixed number of rounds
+    Rounds(u32),
+    /// Play until a player's cumulative score reaches the target
+    TargetScore(u32),
+}
+
+/// The settings for a match of several rounds
+pub struct MatchConfig {
+    pub player_count: usize,
+    pub game_mode: GameMode,
+    pub rules: Rules,
+    pub length: MatchLength,
+    /// Seed for the first round, every following round uses the next seed,
+    /// wrapping around to 0 after `u64::MAX`. Without a seed every round is
+    /// shuffled at random.
+    pub seed: Option<u64>,
+}
+
+impl MatchConfig {
+    pub fn new(player_count: usize, length: MatchLength) -> Self {
+        MatchConfig {
+            player_count,
+            game_mode: GameMode::Random,
+            rules: Rules::default(),
+            length,
+            seed: None,
+        }
+    }
+
+    /// Check the match can be played: at least two players and at least one
+    /// round, or a target score of at least one
+    pub fn validate(&self) -> Result<(), String> {
+        if self.player_count < 2 {
+            return Err("a match needs at least 2 players".to_string());
+        }
+        match self.length {
+            MatchLength::Rounds(0) => Err("a match needs at least 1 round".to_string()),
+            MatchLength::TargetScore(0) => Err("the target score must be at least 1".to_string()),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// The result of a single round of a match
+#[derive(Debug, PartialEq, Eq, Clone, Serialize)]
+pub struct RoundResult {
+    /// Round number, starting at 1
+    pub round: u32,
+    pub dealer_index: usize,
+    pub standings: Standings,
+    /// Each player's score for this round, by player index
+    pub scores: Vec<u32>,
+}
+
+/// The result of a whole match
+#[derive(Debug, PartialEq, Eq, Clone, Serialize)]
+pub struct MatchResult {
+    pub rounds: Vec<RoundResult>,
+    /// Each player's total score over all rounds, by player index
+    pub cumulative_scores: Vec<u32>,
+    pub outcome: EndGameCondition,
+}
+
+/// A match of several rounds of Go Fish between the same players.
+/// The dealer rotates every round and scores are added up across rounds.
+pub struct Match {
+    pub config: MatchConfig,
+    pub rounds: Vec<RoundResult>,
+    pub cumulative_scores: Vec<u32>,
+    /// Print the turn by turn narration of each round
+    pub verbose: bool,
+}
+
+impl Match {
+    pub fn new(config: MatchConfig) -> Self {
+        let cumulative_scores = vec![0; config.player_count];
+        Match {
+            config,
+            rounds: Vec::new(),
+            cumulative_scores,
+            verbose: true,
+        }
+    }
+
+    /// Check if the match has reached its configured length
+    pub fn is_over(&self) -> bool {
+        match self.config.length {
+            MatchLength::Rounds(rounds) => self.rounds.len() as u32 >= rounds,
+            MatchLength::TargetScore(target) => {
+                self.cumulative_scores.iter().any(|&score| score >= target)
+            }
+        }
+    }
+
+    /// Get the dealer for the next round
+    /// The last player deals the first round so the first player starts,
+    /// then the deal moves one seat to the left every round
+    pub fn next_dealer_index(&self) -> usize {
+        (self.config.player_count - 1 + self.rounds.len()) % self.config.player_count
+    }
+
+    /// Play a single round and add its scores to the running totals
+    pub fn play_round(&mut self) -> &RoundResult {
+        let dealer_index = self.next_dealer_index();
+        let rng = match self.config.seed {
+            Some(seed) => StdRng::seed_from_u64(nth_seed(seed, self.rounds.len() as u64)),
+            None => StdRng::from_entropy(),
+        };
+        let mut game = GameState::new_with_rules(
+            vec![self.config.game_mode; self.config.player_count],
+            self.config.rules.clone(),
+            rng,
+        );
+        game.dealer_index = dealer_index;
+        game.verbose = self.verbose;
+        game.deal();
+        game.players_match_cards();
+
+        let standings = run_game(&mut game);
+        let scores: Vec<u32> = game.players.iter().map(|player| player.score).collect();
+        for (total, score) in self.cumulative_scores.iter_mut().zip(&scores) {
+            *total += score;
+        }
+
+        self.rounds.push(RoundResult {
+            round: self.rounds.len() as u32 + 1,
+            dealer_index,
+            standings,
+            scores,
+        });
+        self.rounds.last().unwrap()
+    }
+
+    /// Play rounds until the match is over
+    pub fn play(&mut self) -> MatchResult {
+        while !self.is_over() {
+            self.play_round();
+        }
+        MatchResult {
+            rounds: self.rounds.clone(),
+            cumulative_scores: self.cumulative_scores.clone(),
+            outcome: self.determine_winner(),
+        }
+    }
+
+    /// Determine the overall winner from the cumulative scores
+    pub fn determine_winner(&self) -> EndGameCondition {
+        let max_score = match self.cumulative_scores.iter().max() {
+            Some(&max_score) => max_score,
+            None => return EndGameCondition::Continue,
+        };
+        let winners: Vec<usize> = self
+            .cumulative_scores
+            .iter()
+            .enumerate()
+            .filter(|&(_, &score)| score == max_score)
+            .map(|(index, _)| index)
+            .collect();
+        match winners.len() {
+            1 => EndGameCondition::Winner(winners[0]),
+            _ => EndGameCondition::Tie(winners),
+        }
+    }
+}
+
+impl MatchResult {
+    /// Serialize the match result as pretty printed JSON
+    pub fn to_json(&self) -> String {
+        // Serializing plain structs, enums and strings can not fail
+        serde_json::to_string_pretty(self).unwrap()
+    }
+}
+
+impl fmt::Display for MatchResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:>5}  {:>6}  {:>6}", "Round", "Dealer", "Winner")?;
+        for index in 0..self.cumulative_scores.len() {
+            write!(f, "  {:>4}", format!("P{}", index))?;
+        }
+        writeln!(f)?;
+
+        for round in &self.rounds {
+            let winner = match &round.standings.outcome {
+                EndGameCondition::Winner(index) => format!("P{}", index),
+                EndGameCondition::Tie(_) => "tie".to_string(),
+                EndGameCondition::Continue => "-".to_string(),
+            };
+            write!(
+                f,
+                "{:>5}  {:>6}  {:>6}",
+                round.round, round.dealer_index, winner
+            )?;
+            for score in &round.scores {
+                write!(f, "  {:>4}", score)?;
+            }
+            writeln!(f)?;
+        }
+
+        write!(f, "{:>5}  {:>6}  {:>6}", "Total", "", "")?;
+        for score in &self.cumulative_scores {
+            write!(f, "  {:>4}", score)?;
+        }
+        writeln!(f)?;
+
+        match &self.outcome {
+            EndGameCondition::Winner(index) => {
+                writeln!(f, "The match winner is player at index {}", index)
+            }
+            EndGameCondition::Tie(indices) => {
+                writeln!(
+                    f,
+                    "The match is tied between players at indices {:?}",
+                    indices
+                )
+            }
+            EndGameCondition::Continue => writeln!(f, "The match is still in progress"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup(length: MatchLength) -> Match {
+        let mut config = MatchConfig::new(4, length);
+        config.seed = Some(5);
+        let mut game_match = Match::new(config);
+        game_match.verbose = false;
+        game_match
+    }
+
+    #[test]
+    fn test_match_rounds_rotate_dealer() {
+        let mut game_match = setup(MatchLength::Rounds(5));
+        let result = game_match.play();
+
+        assert_eq!(result.rounds.len(), 5);
+        let dealers: Vec<usize> = result
+            .rounds
+            .iter()
+            .map(|round| round.dealer_index)
+            .collect();
+        assert_eq!(dealers, vec![3, 0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn test_match_cumulative_scores() {
+        let mut game_match = setup(MatchLength::Rounds(3));
+        let result = game_match.play();
+
+        for index in 0..4 {
+            let total: u32 = result.rounds.iter().map(|round| round.scores[index]).sum();
+            assert_eq!(result.cumulative_scores[index], total);
+        }
+        assert_eq!(result.outcome, game_match.determine_winner());
+    }
+
+    #[test]
+    fn test_match_seeded_is_repeatable() {
+        let first = setup(MatchLength::Rounds(3)).play();
+        let second = setup(MatchLength::Rounds(3)).play();
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn test_match_seed_wraps_around() {
+        let mut game_match = setup(MatchLength::Rounds(2));
+        game_match.config.seed = Some(u64::MAX);
+        let result = game_match.play();
+        assert_eq!(result.rounds.len(), 2);
+    }
+
+    #[test]
+    fn test_match_config_validate() {
+        assert_eq!(
+            MatchConfig::new(4, MatchLength::Rounds(1)).validate(),
+            Ok(())
+        );
+        assert!(MatchConfig::new(4, MatchLength::Rounds(0))
+            .validate()
+            .is_err());
+        assert!(MatchConfig::new(4, MatchLength::TargetScore(0))
+            .validate()
+            .is_err());
+        assert!(MatchConfig::new(1, MatchLength::Rounds(3))
+            .validate()
+            .is_err());
+    }
+
+    #[test]
+    fn test_match_target_score() {
+        let mut game_match = setup(MatchLength::TargetScore(20));
+        let result = game_match.play();
+
+        assert!(result.cumulative_scores.iter().any(|&score| score >= 20));
+        let before_last: Vec<u32> = result
+            .cumulative_scores
+            .iter()
+            .zip(&result.rounds.last().unwrap().scores)
+            .map(|(total, score)| total - score)
+            .collect();
+        assert!(before_last.iter().all(|&score| score < 20));
+    }
+}
@@ -0,0 +1,373 @@
+use crate::deck::{is_joker, pairing_rank, pairing_rank_of};
+use cardpack::{Card, Pile, Rank};
+use std::fmt;
+
+/// Number of rank slots in a hand: the thirteen ranks of the deck by weight,
+/// and one more shared by the two jokers
+const RANK_SLOTS: usize = 15;
+
+/// Get the slot counting the cards a card pairs with
+fn slot(rank: Rank) -> usize {
+    pairing_rank_of(rank).weight as usize
+}
+
+/// A player's hand: the cards in the order they were received, or sorted by
+/// frequency before pairs are laid down, with the number of cards held of every
+/// rank kept alongside
+///
+/// Looking up a rank, finding a pair and taking the cards of a rank out check the
+/// counts instead of scanning the cards, and only walk the cards to pick out the
+/// ones they hand back. The jokers pair with each other and share a count. Convert
+/// to and from a `Pile` to display a hand or use the `cardpack` functions.
+///
+/// # Example
+///
+/// ```
+/// use cardpack::Standard52;
+/// use rust_go_fish::deck::parse_rank;
+/// use rust_go_fish::hand::Hand;
+///
+/// let mut hand = Hand::from(Standard52::pile_from_index("5C 9S 5H").unwrap());
+/// assert_eq!(hand.count(parse_rank("5").unwrap()), 2);
+///
+/// let (first, second) = hand.take_pair().unwrap();
+/// assert_eq!((first.index.as_str(), second.index.as_str()), ("5C", "5H"));
+/// assert_eq!(hand.to_index(), "9S");
+/// ```
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct Hand {
+    cards: Vec<Card>,
+    /// Number of cards held of every rank, by slot
+    counts: [u32; RANK_SLOTS],
+    /// A bit for every slot holding two or more cards
+    pairs: u16,
+}
+
+impl Hand {
+    pub fn new() -> Self {
+        Hand::default()
+    }
+
+    /// Get the cards in the order they are held
+    pub fn cards(&self) -> &Vec<Card> {
+        &self.cards
+    }
+
+    pub fn len(&self) -> usize {
+        self.cards.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cards.is_empty()
+    }
+
+    /// Check if the card is held, comparing the card's index so a card whose weight
+    /// `sort_by_frequency` changed still matches
+    pub fn contains(&self, card: &Card) -> bool {
+        self.count(card.rank) > 0 && self.cards.iter().any(|held| held.index == card.index)
+    }
+
+    /// Get the number of cards held that pair with the rank
+    pub fn count(&self, rank: Rank) -> usize {
+        self.counts[slot(rank)] as usize
+    }
+
+    /// Check if any card held pairs with the rank
+    pub fn has_rank(&self, rank: Rank) -> bool {
+        self.count(rank) > 0
+    }
+
+    /// Get the first card held that pairs with the rank
+    pub fn card_of_rank(&self, rank: Rank) -> Option<&Card> {
+        if !self.has_rank(rank) {
+            return None;
+        }
+        let rank = pairing_rank_of(rank);
+        self.cards.iter().find(|card| pairing_rank(card) == rank)
+    }
+
+    /// Get the ranks held, highest first, with each joker as its own rank
+    pub fn ranks(&self) -> Vec<Rank> {
+        self.to_pile().ranks()
+    }
+
+    /// Add a card to the end of the hand
+    pub fn push(&mut self, card: Card) {
+        self.add_count(slot(card.rank));
+        self.cards.push(card);
+    }
+
+    /// Add every card of a pile to the end of the hand
+    pub fn append(&mut self, pile: &Pile) {
+        for card in pile.cards() {
+            self.push(card.clone());
+        }
+    }
+
+    /// Take the card at a position out of the hand
+    ///
+    /// # Panics
+    ///
+    /// If the position is past the end of the hand
+    pub fn remove(&mut self, position: usize) -> Card {
+        let card = self.cards.remove(position);
+        self.remove_count(slot(card.rank));
+        card
+    }
+
+    /// Take the first card with the given card's index out of the hand, if it is held
+    pub fn remove_card(&mut self, card: &Card) -> Option<Card> {
+        if !self.has_rank(card.rank) {
+            return None;
+        }
+        let position = self
+            .cards
+            .iter()
+            .position(|held| held.index == card.index)?;
+        Some(self.remove(position))
+    }
+
+    /// Take the first card held that pairs with the rank out of the hand
+    pub fn take_rank(&mut self, rank: Rank) -> Option<Card> {
+        if !self.has_rank(rank) {
+            return None;
+        }
+        let rank = pairing_rank_of(rank);
+        let position = self
+            .cards
+            .iter()
+            .position(|card| pairing_rank(card) == rank)?;
+        Some(self.remove(position))
+    }
+
+    /// Take every card that pairs with the rank out of the hand
+    pub fn take_all_of_rank(&mut self, rank: Rank) -> Vec<Card> {
+        let mut taken = Vec::with_capacity(self.count(rank));
+        while let Some(card) = self.take_rank(rank) {
+            taken.push(card);
+        }
+        taken
+    }
+
+    /// Take two cards that make a pair out of the hand: the first card to complete
+    /// a pair going through the hand in order, and the earlier card it pairs with
+    pub fn take_pair(&mut self) -> Option<(Card, Card)> {
+        if self.pairs == 0 {
+            return None;
+        }
+        let mut seen = 0u16;
+        let position = self.cards.iter().position(|card| {
+            let bit = 1 << slot(card.rank);
+            let paired = seen & bit != 0;
+            seen |= bit;
+            paired
+        })?;
+        let second = self.remove(position);
+        let first = self.take_rank(second.rank)?;
+        Some((first, second))
+    }
+
+    /// Take `size` cards of the same rank out of the hand, such as a complete
+    /// family in Happy Families: the first rank to reach `size` cards going through
+    /// the hand in order
+    pub fn take_book(&mut self, size: usize) -> Option<Vec<Card>> {
+        if !self.counts.iter().any(|&count| count as usize >= size) {
+            return None;
+        }
+        let mut seen = [0usize; RANK_SLOTS];
+        let rank = self.cards.iter().map(pairing_rank).find(|rank| {
+            seen[slot(*rank)] += 1;
+            seen[slot(*rank)] == size
+        })?;
+        (0..size).map(|_| self.take_rank(rank)).collect()
+    }
+
+    /// Reorder the cards the way `Pile::sort_by_frequency` does, taking the size of
+    /// every rank from the counts
+    ///
+    /// Every card is weighted by its rank shifted left by the square of the number
+    /// of cards of its rank, so the ranks held most often come first, and the cards
+    /// keep those weights. A rank held six or more times, which overflows the
+    /// shift in `Pile::sort_by_frequency`, gets the highest weight instead.
+    pub fn sort_by_frequency(&mut self) {
+        let sizes: Vec<u32> = self
+            .cards
+            .iter()
+            .map(|card| {
+                if is_joker(card) {
+                    self.cards
+                        .iter()
+                        .filter(|held| held.rank == card.rank)
+                        .count() as u32
+                } else {
+                    self.counts[slot(card.rank)]
+                }
+            })
+            .collect();
+        for (card, size) in self.cards.iter_mut().zip(sizes) {
+            card.weight = card
+                .to_rank_weight()
+                .weight
+                .checked_shl(size.saturating_mul(size))
+                .unwrap_or(u32::MAX);
+        }
+        self.cards.sort();
+        self.cards.reverse();
+    }
+
+    /// Copy the hand into a pile in the same order
+    pub fn to_pile(&self) -> Pile {
+        Pile::from_vector(self.cards.clone())
+    }
+
+    /// Get the hand as an index string such as `5C 9S`
+    pub fn to_index(&self) -> String {
+        self.to_pile().to_index()
+    }
+
+    /// Get the hand as an index string such as `5C 9S`
+    #[cfg(test)]
+    pub(crate) fn to_index_str(&self) -> String {
+        self.to_index()
+    }
+
+    fn add_count(&mut self, slot: usize) {
+        self.counts[slot] += 1;
+        if self.counts[slot] >= 2 {
+            self.pairs |= 1 << slot;
+        }
+    }
+
+    fn remove_count(&mut self, slot: usize) {
+        self.counts[slot] -= 1;
+        if self.counts[slot] < 2 {
+            self.pairs &= !(1 << slot);
+        }
+    }
+}
+
+impl From<Pile> for Hand {
+    fn from(pile: Pile) -> Self {
+        let mut hand = Hand::new();
+        hand.append(&pile);
+        hand
+    }
+}
+
+impl From<Vec<Card>> for Hand {
+    fn from(cards: Vec<Card>) -> Self {
+        Hand::from(Pile::from_vector(cards))
+    }
+}
+
+impl From<&Hand> for Pile {
+    fn from(hand: &Hand) -> Self {
+        hand.to_pile()
+    }
+}
+
+impl PartialEq<Pile> for Hand {
+    fn eq(&self, pile: &Pile) -> bool {
+        &self.cards == pile.cards()
+    }
+}
+
+impl PartialEq<Hand> for Pile {
+    fn eq(&self, hand: &Hand) -> bool {
+        hand == self
+    }
+}
+
+impl fmt::Display for Hand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_pile())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::deck::{parse_card, parse_pile, parse_rank};
+
+    fn hand(indexes: &str) -> Hand {
+        Hand::from(parse_pile(indexes).unwrap())
+    }
+
+    #[test]
+    fn test_counts_follow_the_cards() {
+        let mut hand = hand("5C 9S 5H 5D");
+        let five = parse_rank("5").unwrap();
+        assert_eq!(hand.count(five), 3);
+        assert_eq!(hand.take_rank(five).unwrap().index, "5C");
+        assert_eq!(hand.count(five), 2);
+        assert_eq!(hand.remove(0).index, "9S");
+        assert!(!hand.has_rank(parse_rank("9").unwrap()));
+        assert_eq!(hand.take_all_of_rank(five).len(), 2);
+        assert!(hand.is_empty());
+        assert_eq!(hand, Hand::new());
+    }
+
+    #[test]
+    fn test_take_pair_and_book() {
+        let mut hand = hand("KS 9S 2C KH 9D 9H");
+        let (first, second) = hand.take_pair().unwrap();
+        assert_eq!((first.index.as_str(), second.index.as_str()), ("KS", "KH"));
+        assert_eq!(hand.to_index(), "9S 2C 9D 9H");
+        assert!(hand.take_book(4).is_none());
+        assert_eq!(hand.take_book(3).unwrap().len(), 3);
+        assert!(hand.take_pair().is_none());
+        assert_eq!(hand.to_index(), "2C");
+    }
+
+    #[test]
+    fn test_sort_by_frequency_matches_pile() {
+        for indexes in ["5C 9S 5H KD 9D 5D", "JBT 2C JLT AS", "QS"] {
+            let pile = parse_pile(indexes).unwrap();
+            let mut hand = Hand::from(pile.clone());
+            hand.sort_by_frequency();
+            assert_eq!(hand, pile.sort_by_frequency());
+        }
+    }
+
+    #[test]
+    fn test_sorted_cards_are_found_by_index() {
+        let mut hand = hand("5C 9S 5H KD");
+        hand.sort_by_frequency();
+        let five = parse_card("5H").unwrap();
+        assert!(hand.contains(&five));
+        assert_eq!(hand.remove_card(&five).unwrap().index, "5H");
+        assert!(!hand.contains(&five));
+        assert_eq!(hand.len(), 3);
+        assert!(hand.remove_card(&parse_card("KD").unwrap()).is_some());
+        assert!(hand.remove_card(&parse_card("QD").unwrap()).is_none());
+    }
+
+    #[test]
+    fn test_counts_do_not_overflow() {
+        let ace = parse_card("AS").unwrap();
+        let mut hand = Hand::from(vec![ace.clone(); 300]);
+        assert_eq!(hand.count(ace.rank), 300);
+        hand.sort_by_frequency();
+        assert_eq!(hand.take_all_of_rank(ace.rank).len(), 300);
+    }
+
+    #[test]
+    fn test_jokers_pair_with_each_other() {
+        let mut hand = hand("JBT 5C JLT");
+        assert_eq!(hand.count(parse_rank("5").unwrap()), 1);
+        let (first, second) = hand.take_pair().unwrap();
+        assert_eq!(
+            (first.index.as_str(), second.index.as_str()),
+            ("JBT", "JLT")
+        );
+    }
+
+    #[test]
+    fn test_pile_conversion_keeps_the_order() {
+        let pile = parse_pile("QD 3S QC").unwrap();
+        let hand = Hand::from(pile.clone());
+        assert_eq!(hand, pile);
+        assert_eq!(hand.to_pile(), pile);
+        assert_eq!(hand.to_string(), pile.to_string());
+    }
+}
@@ -0,0 +1,292 @@
+use crate::deck::{is_joker, pairing_rank};
+use crate::search::{score_asks, Ask, SearchConfig};
+use crate::tracker::CardTracker;
+use crate::view::PlayerView;
+use cardpack::{Card, Named};
+use rand::Rng;
+use std::fmt;
+
+/// The ask recommended to a player, why it is recommended and how likely every
+/// other player is to hold each rank
+#[derive(Debug, Clone)]
+pub struct Hint {
+    /// The player the hint is for
+    pub player_index: usize,
+    pub ask: Ask,
+    /// The card in the player's hand whose rank to ask for
+    pub card: Card,
+    /// Why the ask is recommended, in plain words
+    pub explanation: String,
+    /// The ranks in play, one of each that pairs together
+    pub ranks: Vec<Card>,
+    /// Estimated probability that a player holds a card of each rank, by player
+    /// index and then in the order of `ranks`. The row of the player the hint is
+    /// for counts their own hand.
+    pub probabilities: Vec<Vec<f64>>,
+}
+
+impl fmt::Display for Hint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", self.explanation)?;
+        write!(f, "{:>8}", "Player")?;
+        for card in &self.ranks {
+            write!(f, " {:>4}", rank_index(card))?;
+        }
+        writeln!(f)?;
+        for (player_index, row) in self.probabilities.iter().enumerate() {
+            if player_index == self.player_index {
+                continue;
+            }
+            write!(f, "{:>8}", player_index)?;
+            for probability in row {
+                write!(f, " {:>3.0}%", probability * 100.0)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// Get the short name of a card's rank, such as `7`, `10` or `K`, and `Jk` for the jokers
+fn rank_index(card: &Card) -> String {
+    if is_joker(card) {
+        return "Jk".to_string();
+    }
+    match card.rank.name.index_default().as_str() {
+        "T" => "10".to_string(),
+        index => index.to_string(),
+    }
+}
+
+/// Get the name of the rank asked for in an explanation, such as `7s` or `the Bun family`
+fn rank_name(view: &PlayerView, card: &Card) -> String {
+    if let Some(name) = view
+        .rules
+        .families
+        .as_ref()
+        .and_then(|families| families.family_name(card))
+    {
+        return format!("the {} family", name);
+    }
+    if is_joker(card) {
+        return "jokers".to_string();
+    }
+    format!("{}s", rank_index(card))
+}
+
+/// Get the ask the search strategy recommends, with an explanation a new
+/// player can follow and the odds of every other player holding each rank
+///
+/// The odds come from a card tracker following the game from the player's seat.
+///
+/// # Example
+///
+/// ```
+/// use rust_go_fish::hint::hint;
+/// use rust_go_fish::search::SearchConfig;
+/// use rust_go_fish::tracker::CardTracker;
+/// use rust_go_fish::view::PlayerView;
+/// use rust_go_fish::{GameMode, GameState};
+/// use rand::rngs::StdRng;
+/// use rand::SeedableRng;
+///
+/// let mut game = GameState::new_seeded(vec![GameMode::Random; 3], 3);
+/// game.verbose = false;
+/// game.deal();
+/// game.players_match_cards();
+///
+/// let view = PlayerView::from_game(&game, 0);
+/// let config = SearchConfig { determinizations: 2, rollout_turns: 20 };
+/// let tracker = CardTracker::from_game(&game, Some(0));
+/// let hint = hint(&view, &tracker, &config, &mut StdRng::seed_from_u64(1));
+/// assert!(game.players[0].hand.contains(&hint.card));
+/// assert!(!hint.explanation.is_empty());
+/// ```
+pub fn hint<R: Rng + ?Sized>(
+    view: &PlayerView,
+    tracker: &CardTracker,
+    config: &SearchConfig,
+    rng: &mut R,
+) -> Hint {
+    let scores = score_asks(view, config, rng);
+    let ask = scores
+        .iter()
+        .fold(None, |best: Option<&(Ask, f64)>, scored| match best {
+            Some(best) if best.1 >= scored.1 => Some(best),
+            _ => Some(scored),
+        })
+        .map(|(ask, _)| *ask)
+        .unwrap_or(Ask {
+            card_index: 0,
+            other_player_index: (view.player_index + 1) % view.player_count(),
+        });
+
+    let ranks = tracker.ranks.clone();
+    let probabilities = tracker.holding_probabilities();
+    let card = view.hand.cards().get(ask.card_index).cloned();
+    let explanation = match &card {
+        Some(card) => explain(view, ask, card, &ranks, &probabilities),
+        None => "You have no cards to ask with.".to_string(),
+    };
+    Hint {
+        player_index: view.player_index,
+        ask,
+        card: card.unwrap_or_default(),
+        explanation,
+        ranks,
+        probabilities,
+    }
+}
+
+/// Explain an ask with the public reason to expect the other player holds the rank
+fn explain(
+    view: &PlayerView,
+    ask: Ask,
+    card: &Card,
+    ranks: &[Card],
+    probabilities: &[Vec<f64>],
+) -> String {
+    let other = ask.other_player_index;
+    let rank = pairing_rank(card);
+    let name = rank_name(view, card);
+    let held = view
+        .hand
+        .cards()
+        .iter()
+        .filter(|held| pairing_rank(held) == rank)
+        .count();
+    let probability = ranks
+        .iter()
+        .position(|ranked| pairing_rank(ranked) == rank)
+        .map_or(0.0, |slot| probabilities[other][slot]);
+
+    let mut explanation = format!("Ask player {} for {}.", other, name);
+    let last_ask = view
+        .asks
+        .iter()
+        .rev()
+        .find(|record| record.asker == other && pairing_rank(&record.card) == rank);
+    let still_known = view.known_ranks()[other].contains(&card.rank);
+    match last_ask {
+        Some(record) if still_known => {
+            let turns_ago = match view.turns.saturating_sub(record.turn) {
+                0 => "this turn".to_string(),
+                1 => "1 turn ago".to_string(),
+                turns => format!("{} turns ago", turns),
+            };
+            explanation.push_str(&format!(
+                " Player {} asked for {} {} and hasn't laid them down, so they still hold one.",
+                other, name, turns_ago
+            ));
+        }
+        _ => {
+            explanation.push_str(&format!(
+                " Nobody is known to hold {}, but with {} cards in hand player {} holds one {:.0}% of the time.",
+                name,
+                view.hand_sizes[other],
+                other,
+                probability * 100.0
+            ));
+        }
+    }
+    let book = if view.rules.families.is_some() {
+        "family"
+    } else {
+        "book"
+    };
+    if held + 1 >= view.rules.book_size {
+        explanation.push_str(&format!(" One more card completes your {}.", book));
+    } else {
+        explanation.push_str(&format!(
+            " You hold {} of them and need {} for a {}.",
+            held, view.rules.book_size, book
+        ));
+    }
+    explanation
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::deck::parse_card;
+    use crate::game::{GameMode, GameState};
+    use crate::scenario::Scenario;
+    use crate::Rules;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn config() -> SearchConfig {
+        SearchConfig {
+            determinizations: 4,
+            rollout_turns: 40,
+        }
+    }
+
+    fn setup() -> GameState {
+        Scenario::new(Rules::default())
+            .player(GameMode::Random, "7C 9S")
+            .player(GameMode::Random, "5H KD 2C")
+            .player(GameMode::Random, "7H QD 3S")
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_hint_explains_a_known_rank() {
+        let mut game = setup();
+        // player 2 asks player 1 for sevens and goes fishing
+        game.turns = 1;
+        game.dealer_index = 1;
+        game.perform_ask(2, 1, parse_card("7H").unwrap());
+        game.turns = 3;
+
+        let view = PlayerView::from_game(&game, 0);
+        let tracker = CardTracker::from_game(&game, Some(0));
+        let hint = hint(&view, &tracker, &config(), &mut StdRng::seed_from_u64(2));
+        assert_eq!(hint.ask.other_player_index, 2);
+        assert_eq!(rank_index(&hint.card), "7");
+        assert_eq!(
+            hint.explanation,
+            "Ask player 2 for 7s. Player 2 asked for 7s 2 turns ago and hasn't laid them down, \
+             so they still hold one. One more card completes your book."
+        );
+
+        // player 2 asked for sevens and player 1 had none to hand over
+        let seven = hint
+            .ranks
+            .iter()
+            .position(|card| rank_index(card) == "7")
+            .unwrap();
+        assert_eq!(hint.probabilities[2][seven], 1.0);
+        assert_eq!(hint.probabilities[1][seven], 0.0);
+        assert_eq!(hint.probabilities[0][seven], 1.0);
+    }
+
+    #[test]
+    fn test_hint_odds_come_from_the_tracker() {
+        let game = setup();
+        let view = PlayerView::from_game(&game, 0);
+        let tracker = CardTracker::from_game(&game, Some(0));
+        let hint = hint(&view, &tracker, &config(), &mut StdRng::seed_from_u64(1));
+        assert_eq!(hint.ranks.len(), 13);
+        assert_eq!(hint.probabilities, tracker.holding_probabilities());
+        assert!(hint
+            .probabilities
+            .iter()
+            .flatten()
+            .all(|probability| (0.0..=1.0).contains(probability)));
+    }
+
+    #[test]
+    fn test_hint_display_lists_opponents() {
+        let game = setup();
+        let view = PlayerView::from_game(&game, 0);
+        let tracker = CardTracker::from_game(&game, Some(0));
+        let text = hint(&view, &tracker, &config(), &mut StdRng::seed_from_u64(3)).to_string();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 4);
+        assert!(lines[1].trim_start().starts_with("Player"));
+        assert!(lines[2].trim_start().starts_with('1'));
+        assert!(lines[3].trim_start().starts_with('2'));
+    }
+}
@@ -0,0 +1,304 @@
+use crate::audit::RuleViolation;
+use crate::game::{EndGameCondition, GameEvent, GameState, PlayerTurnResult};
+use cardpack::Card;
+use std::fmt;
+
+/// How a table is played, which decides whether actions can be taken back
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum PlayMode {
+    /// Interactive and teaching play, where any action can be undone and redone
+    Casual,
+    /// Play that counts, such as a match, a tournament or a networked table, where
+    /// actions are final and no snapshots are kept
+    Competitive,
+}
+
+/// Why an action could not be undone or redone
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum UndoError {
+    /// The table is played competitively
+    Disabled,
+    NothingToUndo,
+    NothingToRedo,
+}
+
+impl fmt::Display for UndoError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UndoError::Disabled => write!(f, "undo is disabled in competitive play"),
+            UndoError::NothingToUndo => write!(f, "there is nothing to undo"),
+            UndoError::NothingToRedo => write!(f, "there is nothing to redo"),
+        }
+    }
+}
+
+impl std::error::Error for UndoError {}
+
+/// The game and the player whose turn it is, as they were at one point of play
+#[derive(Clone)]
+struct Snapshot {
+    game: GameState,
+    current_player: usize,
+}
+
+/// A game played one action at a time that can take actions back and replay them
+///
+/// An action is one turn: the ask with the cards it hands over, the go fish
+/// draw and the books laid down after it, or the turn of a player without
+/// cards. A snapshot of the game is kept before every action, so undoing puts
+/// back every card, score, counter and the random number generator exactly as
+/// they were. Playing a new action after an undo drops the actions that could
+/// have been redone.
+///
+/// # Example
+///
+/// ```
+/// use rust_go_fish::history::{GameHistory, PlayMode};
+/// use rust_go_fish::{GameMode, GameState};
+///
+/// let mut game = GameState::new_seeded(vec![GameMode::Random; 3], 2);
+/// game.verbose = false;
+/// game.deal();
+/// game.players_match_cards();
+/// let deck = game.deck.clone();
+///
+/// let mut history = GameHistory::new(game, PlayMode::Casual);
+/// history.play_turn().unwrap();
+/// let undone = history.undo().unwrap();
+/// assert!(!undone.is_empty());
+/// assert_eq!(history.game.deck, deck);
+///
+/// history.redo().unwrap();
+/// assert!(!history.can_redo());
+/// ```
+pub struct GameHistory {
+    pub game: GameState,
+    /// The player whose turn it is
+    pub current_player: usize,
+    pub play_mode: PlayMode,
+    undo_stack: Vec<Snapshot>,
+    redo_stack: Vec<Snapshot>,
+}
+
+impl GameHistory {
+    /// Start keeping the history of a dealt game, from the turn of its first player
+    pub fn new(game: GameState, play_mode: PlayMode) -> Self {
+        let current_player = game.first_player_index();
+        GameHistory {
+            game,
+            current_player,
+            play_mode,
+            undo_stack: Vec::new(),
+            redo_stack: Vec::new(),
+        }
+    }
+
+    /// Check if there is an action to undo
+    pub fn can_undo(&self) -> bool {
+        self.play_mode == PlayMode::Casual && !self.undo_stack.is_empty()
+    }
+
+    /// Check if there is an undone action to redo
+    pub fn can_redo(&self) -> bool {
+        self.play_mode == PlayMode::Casual && !self.redo_stack.is_empty()
+    }
+
+    /// Get whether the game is over and who won
+    pub fn outcome(&self) -> EndGameCondition {
+        self.game.check_win_condition()
+    }
+
+    /// Play the current player's turn the way their game mode picks it
+    ///
+    /// A turn that breaks a rule leaves the game as it was.
+    pub fn play_turn(&mut self) -> Result<PlayerTurnResult, RuleViolation> {
+        let current_player = self.current_player;
+        self.act(|game| game.try_play_turn(current_player))
+    }
+
+    /// Make the current player ask another player for a card, for a player
+    /// choosing their own asks
+    ///
+    /// An ask that breaks a rule leaves the game as it was.
+    pub fn ask(
+        &mut self,
+        other_player_index: usize,
+        card: Card,
+    ) -> Result<PlayerTurnResult, RuleViolation> {
+        let current_player = self.current_player;
+        self.act(|game| {
+            game.turns += 1;
+            game.try_perform_ask(current_player, other_player_index, card)
+        })
+    }
+
+    /// Play an action, keeping a snapshot to undo it unless play is competitive
+    fn act<F>(&mut self, action: F) -> Result<PlayerTurnResult, RuleViolation>
+    where
+        F: FnOnce(&mut GameState) -> Result<PlayerTurnResult, RuleViolation>,
+    {
+        let before = self.snapshot();
+        let result = match action(&mut self.game) {
+            Ok(result) => result,
+            Err(violation) => {
+                self.restore(before);
+                return Err(violation);
+            }
+        };
+        if result == PlayerTurnResult::NextPlayerTurn {
+            self.current_player = self.game.next_player_index(self.current_player);
+        }
+        if self.play_mode == PlayMode::Casual {
+            self.undo_stack.push(before);
+            self.redo_stack.clear();
+        }
+        Ok(result)
+    }
+
+    /// Take back the last action and get the events it recorded
+    pub fn undo(&mut self) -> Result<Vec<GameEvent>, UndoError> {
+        if self.play_mode == PlayMode::Competitive {
+            return Err(UndoError::Disabled);
+        }
+        let before = self.undo_stack.pop().ok_or(UndoError::NothingToUndo)?;
+        let undone = self.game.events[before.game.events.len()..].to_vec();
+        let after = self.snapshot();
+        self.restore(before);
+        self.redo_stack.push(after);
+        Ok(undone)
+    }
+
+    /// Play the last undone action again and get the events it recorded
+    pub fn redo(&mut self) -> Result<Vec<GameEvent>, UndoError> {
+        if self.play_mode == PlayMode::Competitive {
+            return Err(UndoError::Disabled);
+        }
+        let after = self.redo_stack.pop().ok_or(UndoError::NothingToRedo)?;
+        let redone = after.game.events[self.game.events.len()..].to_vec();
+        let before = self.snapshot();
+        self.restore(after);
+        self.undo_stack.push(before);
+        Ok(redone)
+    }
+
+    fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            game: self.game.clone(),
+            current_player: self.current_player,
+        }
+    }
+
+    fn restore(&mut self, snapshot: Snapshot) {
+        self.game = snapshot.game;
+        self.current_player = snapshot.current_player;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::deck::parse_card;
+    use crate::game::GameMode;
+    use crate::scenario::Scenario;
+    use crate::Rules;
+
+    fn setup(play_mode: PlayMode) -> GameHistory {
+        let mut game = GameState::new_seeded(vec![GameMode::Sequential; 2], 4);
+        game.verbose = false;
+        game.deal();
+        game.players_match_cards();
+        GameHistory::new(game, play_mode)
+    }
+
+    #[test]
+    fn test_undo_and_redo_an_ask() {
+        let game = Scenario::new(Rules::default())
+            .player(GameMode::Sequential, "5C 9S")
+            .player(GameMode::Sequential, "5H KD")
+            .build()
+            .unwrap();
+        let mut history = GameHistory::new(game, PlayMode::Casual);
+        let hand = history.game.players[0].hand.clone();
+
+        let result = history.ask(1, parse_card("5C").unwrap()).unwrap();
+        assert_eq!(result, PlayerTurnResult::PlayAgain);
+        assert_eq!(history.game.players[0].score, 1);
+        assert_eq!(history.game.turns, 1);
+
+        let undone = history.undo().unwrap();
+        assert_eq!(
+            undone.first().map(|event| event.to_string()),
+            Some("player 0 asks player 1 for 5C".to_string())
+        );
+        assert_eq!(history.game.players[0].hand, hand);
+        assert_eq!(history.game.players[0].score, 0);
+        assert_eq!(history.game.turns, 0);
+        assert_eq!(history.game.events.len(), 2);
+
+        let redone = history.redo().unwrap();
+        assert_eq!(redone, undone);
+        assert_eq!(history.game.players[0].score, 1);
+        assert_eq!(history.redo(), Err(UndoError::NothingToRedo));
+    }
+
+    #[test]
+    fn test_undo_every_turn_back_to_the_start() {
+        let mut history = setup(PlayMode::Casual);
+        let deck = history.game.deck.clone();
+        let first_player = history.current_player;
+        let mut turns = 0;
+        while history.outcome() == EndGameCondition::Continue && turns < 10 {
+            history.play_turn().unwrap();
+            turns += 1;
+        }
+        let transcript = history.game.transcript();
+
+        while history.can_undo() {
+            history.undo().unwrap();
+        }
+        assert_eq!(history.game.deck, deck);
+        assert_eq!(history.current_player, first_player);
+        assert_eq!(history.undo(), Err(UndoError::NothingToUndo));
+
+        while history.can_redo() {
+            history.redo().unwrap();
+        }
+        assert_eq!(history.game.transcript(), transcript);
+    }
+
+    #[test]
+    fn test_new_action_drops_redo() {
+        let mut history = setup(PlayMode::Casual);
+        history.play_turn().unwrap();
+        history.undo().unwrap();
+        assert!(history.can_redo());
+        history.play_turn().unwrap();
+        assert!(!history.can_redo());
+    }
+
+    #[test]
+    fn test_refused_ask_leaves_game_untouched() {
+        let mut history = setup(PlayMode::Casual);
+        let events = history.game.events.len();
+        let current_player = history.current_player;
+        assert_eq!(
+            history.ask(
+                current_player,
+                history.game.players[current_player].hand.cards()[0].clone()
+            ),
+            Err(RuleViolation::AskedSelf(current_player))
+        );
+        assert_eq!(history.game.turns, 0);
+        assert_eq!(history.game.events.len(), events);
+        assert!(!history.can_undo());
+    }
+
+    #[test]
+    fn test_competitive_play_can_not_undo() {
+        let mut history = setup(PlayMode::Competitive);
+        history.play_turn().unwrap();
+        assert!(!history.can_undo());
+        assert_eq!(history.undo(), Err(UndoError::Disabled));
+        assert_eq!(history.redo(), Err(UndoError::Disabled));
+    }
+}
@@ -1,3 +1,49 @@
+pub mod audit;
+pub mod deck;
+pub mod env;
+pub mod families;
+pub mod game;
+pub mod game_match;
+pub mod hand;
+pub mod hint;
+pub mod history;
+pub mod old_maid;
+pub mod ratings;
+pub mod rules;
+pub mod scenario;
+pub mod search;
+pub mod solver;
+pub mod spectator;
+pub mod standings;
+pub mod stats;
+pub mod tables;
+pub mod tournament;
+pub mod tracker;
+pub mod view;
+
+pub use audit::RuleViolation;
+pub use deck::DeckConfig;
+pub use families::Families;
+pub use game::{
+    run_game, run_game_from, try_run_game, try_run_game_from, try_run_game_observed, AskRecord,
+    EndGameCondition, GameEvent, GameMode, GameState, Player,
+};
+pub use game_match::{Match, MatchConfig, MatchLength, MatchResult};
+pub use hand::Hand;
+pub use history::{GameHistory, PlayMode};
+pub use old_maid::{OldMaid, OldMaidResult};
+pub use ratings::{RatingError, RatingStore};
+pub use rules::{Rules, TieBreaker, WildCards, WildRules};
+pub use scenario::Scenario;
+pub use search::SearchConfig;
+pub use spectator::{Perspective, SpectatorStream};
+pub use standings::{PlayerStanding, Standings};
+pub use stats::{GameRecord, StatsReport};
+pub use tables::{Table, TableManager, TableResult};
+pub use tournament::{
+    Entrant, Pairing, Tournament, TournamentConfig, TournamentGame, TournamentResult,
+};
+
 use rand::distributions::uniform::SampleUniform;
 use rand::Rng;
 use std::ops::Range;
@@ -14,18 +60,44 @@ use std::ops::Range;
 /// use rust_go_fish::get_random;
 ///
 /// let num = get_random(0..10);
-/// assert!(num >= 0 && num < 10);
+/// assert!((0..10).contains(&num));
 /// ```
 pub fn get_random<T>(range: Range<T>) -> T
 where
     T: PartialOrd + Copy + SampleUniform,
+{
+    let mut rng = rand::thread_rng(); // Create a random number generator
+    get_random_from(&mut rng, range)
+}
+
+/// Generate a random number within a range using the given random number generator
+///
+/// # Arguments
+///
+/// * `rng` - The random number generator to draw from
+/// * `range` - The range of numbers to generate from
+///
+/// # Example
+///
+/// ```
+/// use rand::rngs::StdRng;
+/// use rand::SeedableRng;
+/// use rust_go_fish::get_random_from;
+///
+/// let mut rng = StdRng::seed_from_u64(7);
+/// let num = get_random_from(&mut rng, 0..10);
+/// assert!((0..10).contains(&num));
+/// ```
+pub fn get_random_from<T, R>(rng: &mut R, range: Range<T>) -> T
+where
+    T: PartialOrd + Copy + SampleUniform,
+    R: Rng + ?Sized,
 {
     if range.start >= range.end {
         // return None; // Return None for an empty or invalid range
         return range.start;
     }
 
-    let mut rng = rand::thread_rng(); // Create a random number generator
     rng.gen_range(range.start..range.end) // Generate a random number
 }
 
@@ -49,7 +121,22 @@ where
     T: PartialOrd + Copy + SampleUniform,
 {
     let mut rng = rand::thread_rng(); // Create a random number generator
+    get_random_excluding_from(&mut rng, range, exclude)
+}
 
+/// Generate a random number within a range, excluding a specific number,
+/// using the given random number generator
+///
+/// # Arguments
+///
+/// * `rng` - The random number generator to draw from
+/// * `range` - The range of numbers to generate from
+/// * `exclude` - The number to exclude
+pub fn get_random_excluding_from<T, R>(rng: &mut R, range: Range<T>, exclude: T) -> T
+where
+    T: PartialOrd + Copy + SampleUniform,
+    R: Rng + ?Sized,
+{
     loop {
         let num = rng.gen_range(range.start..range.end); // Generate a random number
         if num != exclude {
@@ -58,6 +145,23 @@ where
     }
 }
 
+/// The seed `offset` steps after `first_seed`, wrapping around to 0 after `u64::MAX`
+///
+/// Matches, tournaments and tables all seed their games one after another from a
+/// single starting seed with this.
+///
+/// # Example
+///
+/// ```
+/// use rust_go_fish::nth_seed;
+///
+/// assert_eq!(nth_seed(7, 2), 9);
+/// assert_eq!(nth_seed(u64::MAX, 1), 0);
+/// ```
+pub fn nth_seed(first_seed: u64, offset: u64) -> u64 {
+    first_seed.wrapping_add(offset)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -65,13 +169,13 @@ mod tests {
     #[test]
     fn test_get_random() {
         let num = get_random(0..10);
-        assert!(num >= 0 && num < 10);
+        assert!((0..10).contains(&num));
     }
 
     #[test]
     fn test_get_random_with_empty_range() {
         let num = get_random(0..0);
-        assert!(num >= 0 && num < 10);
+        assert!((0..10).contains(&num));
     }
 
     #[test]
@@ -79,4 +183,27 @@ mod tests {
         let num = get_random_excluding(0..10, 5);
         assert_ne!(num, 5);
     }
+
+    #[test]
+    fn test_get_random_from_seed_is_repeatable() {
+        use rand::rngs::StdRng;
+        use rand::SeedableRng;
+
+        let mut first = StdRng::seed_from_u64(42);
+        let mut second = StdRng::seed_from_u64(42);
+        let first_nums: Vec<u32> = (0..8)
+            .map(|_| get_random_from(&mut first, 0..100))
+            .collect();
+        let second_nums: Vec<u32> = (0..8)
+            .map(|_| get_random_from(&mut second, 0..100))
+            .collect();
+        assert_eq!(first_nums, second_nums);
+    }
+
+    #[test]
+    fn test_nth_seed_wraps_around() {
+        assert_eq!(nth_seed(1, 0), 1);
+        assert_eq!(nth_seed(u64::MAX - 1, 1), u64::MAX);
+        assert_eq!(nth_seed(u64::MAX, 3), 2);
+    }
 }
@@ -1,581 +1,609 @@
-use cardpack::{Card, Pile, Rank, Standard52};
-use rust_go_fish::{get_random, get_random_excluding};
-use std::collections::HashMap;
-
+use cardpack::{Card, Standard52};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use rust_go_fish::deck::{parse_card, parse_rank};
+use rust_go_fish::families::FAMILY_SIZE;
+use rust_go_fish::hint::hint;
+use rust_go_fish::ratings::{placements_from_outcome, placements_from_standings};
+use rust_go_fish::search::SearchConfig;
+use rust_go_fish::spectator::{Perspective, SpectatorStream};
+use rust_go_fish::stats::{append_record, load_records};
+use rust_go_fish::tracker::CardTracker;
+use rust_go_fish::view::PlayerView;
+use rust_go_fish::{
+    run_game, try_run_game, try_run_game_from, try_run_game_observed, DeckConfig, EndGameCondition,
+    Entrant, Families, GameHistory, GameMode, GameRecord, GameState, Match, MatchConfig,
+    MatchLength, OldMaid, Pairing, PlayMode, RatingStore, RuleViolation, Rules, Scenario,
+    Standings, StatsReport, TieBreaker, Tournament, TournamentConfig, WildCards, WildRules,
+};
+use std::env;
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+
+#[allow(dead_code)]
 enum Environment {
     Development,
     Test,
     Production,
 }
 
-enum GameMode {
-    Random,
-    Sequential,
-}
-//
-// #[derive(Debug, PartialEq, Eq, Hash, Clone)]
-// enum EndGameConditions {
-//     FirstPlayerWithNoCards,
-//     DeckEmpty,
-// }
-
-#[derive(Debug, PartialEq, Eq, Hash, Clone)]
-enum EndGameCondition {
-    Winner(usize),
-    Tie(Vec<usize>),
-    Continue,
-}
+/// Rating file used when `--ratings` is not given to the ladder and history commands
+const DEFAULT_RATINGS_PATH: &str = "ratings.json";
 
-#[derive(Debug, PartialEq, Eq, Hash, Clone)]
-enum PlayerTurnResult {
-    NextPlayerTurn,
-    PlayAgain,
-}
+/// Statistics file used when `--stats` is not given to the report command
+const DEFAULT_STATS_PATH: &str = "games.jsonl";
 
-#[derive(Debug, PartialEq, Eq, Hash, Clone)]
-enum AskCardResult {
-    ReceiveCard(Card),
-    GoFish,
-}
+/// The seat of the person playing at the terminal
+const HUMAN_INDEX: usize = 0;
 
-fn find_pair(pile: &Pile) -> Option<(Card, Card)> {
-    let mut rank_count = HashMap::new();
-
-    for card in pile.cards() {
-        let count = rank_count.entry(card.rank).or_insert(0);
-        *count += 1;
-
-        if *count == 2 {
-            // Find the first card that matches this rank
-            let first_card = pile.cards().iter().find(|&c| c.rank == card.rank).unwrap();
-
-            return Some((first_card.clone(), card.clone()));
-        }
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    if let Err(error) = run(&args) {
+        eprintln!("{}", error);
+        std::process::exit(2);
     }
-    None
 }
 
-struct Player {
-    hand: Pile,
-    pairs: Pile,
-    score: u32,
-}
+fn run(args: &[String]) -> Result<(), String> {
+    let json_output = args.iter().any(|arg| arg == "--json");
+    let ratings_path = value_of(args, "--ratings")?.map(PathBuf::from);
+    let stats_path = value_of(args, "--stats")?.map(PathBuf::from);
 
-impl Player {
-    fn new() -> Self {
-        Player {
-            hand: Pile::default(),
-            pairs: Pile::default(),
-            score: 0,
+    match args.get(1).map(String::as_str) {
+        Some("ladder") => {
+            let path = ratings_path.unwrap_or(PathBuf::from(DEFAULT_RATINGS_PATH));
+            return show_ladder(&path);
         }
-    }
-
-    /// Add a card to the player's hand
-    fn add_card(&mut self, card: Card) {
-        self.hand.push(card);
-    }
-
-    /// Add cards to the player's hand
-    fn add_cards(&mut self, cards: Pile) {
-        self.hand.append(&cards);
-    }
-
-    /// Provide a answer with the card of the given rank
-    /// for the player asking it
-    fn answer_for_card_rank(&self, rank: Rank) -> Option<Card> {
-        // Check if the player has a card of the given rank
-        // If so, return it
-        // If not, return None
-        // println!("self.hand.ranks(): {:?}", self.hand.ranks());
-        self.hand.ranks().iter().find(|&r| r == &rank).map(|r| {
-            self.hand
-                .cards()
-                .iter()
-                .find(|&c| c.rank == *r)
-                .unwrap()
-                .clone()
-        })
-        // self.hand.cards().iter().find(|&c| c.rank == rank).map(|c| c.clone())
-    }
-
-    /// Match pairs in the player's hand
-    /// Remove the pairs from the hand and add them to the pairs pile
-    /// Return the number of pairs found
-    fn match_pairs(&mut self) -> u32 {
-        // Check for pairs
-        // If pair, move to pairs pile and add to the score
-        self.hand = self.hand.sort_by_frequency();
-        let mut pairs_found_count = 0;
-        while let Some((card1, card2)) = find_pair(&self.hand) {
-            self.hand.remove_card(&card1);
-            self.hand.remove_card(&card2);
-            self.pairs.push(card1);
-            self.pairs.push(card2);
-            self.score += 1;
-            pairs_found_count += 1;
+        Some("history") => {
+            let name = args.get(2).ok_or("history needs a player name")?;
+            let path = ratings_path.unwrap_or(PathBuf::from(DEFAULT_RATINGS_PATH));
+            return show_history(&path, name);
         }
-        pairs_found_count
-    }
-}
-
-struct GameState {
-    deck: Standard52,
-    players: Vec<Player>,
-    player_count: usize,
-    game_mode: GameMode,
-}
-
-impl GameState {
-    /// Create a new game with a shuffled deck
-    fn new(player_count: usize) -> Self {
-        let deck = Standard52::new_shuffled();
-        // create players
-        let players = (0..player_count).map(|_| Player::new()).collect();
-        GameState {
-            deck,
-            players,
-            player_count,
-            game_mode: GameMode::Random,
+        Some("report") => {
+            let path = stats_path.unwrap_or(PathBuf::from(DEFAULT_STATS_PATH));
+            let records = load_records(&path).map_err(|error| error.to_string())?;
+            print!("{}", StatsReport::from_records(&records));
+            return Ok(());
         }
+        _ => {}
     }
 
-    /// Create a new game with a specific deck
-    fn new_with_deck(player_count: usize, deck: Standard52, game_mode: GameMode) -> Self {
-        // create players
-        let players = (0..player_count).map(|_| Player::new()).collect();
-        GameState {
-            deck,
-            players,
-            player_count,
-            game_mode,
-        }
-    }
+    let view = value_of(args, "--view")?
+        .map(|view| view.parse::<Perspective>())
+        .transpose()?;
 
-    /// Deal cards to each player
-    fn deal(&mut self) {
-        // let hand_size = 52 / self.player_count;
-        let hand_size = 7;
-        for _ in 0..hand_size {
-            for player in &mut self.players {
-                player.add_cards(self.deck.draw(1).unwrap());
-            }
+    if let Some(path) = value_of(args, "--scenario")? {
+        let scenario = Scenario::load(Path::new(path)).map_err(|error| error.to_string())?;
+        let mut game = scenario.build().map_err(|error| error.to_string())?;
+        game.verbose = !json_output && view.is_none();
+        game.audit = args.iter().any(|arg| arg == "--audit");
+        let standings = match &view {
+            Some(perspective) => watch_game(&mut game, scenario.current_player, *perspective),
+            None => try_run_game_from(&mut game, scenario.current_player),
         }
+        .map_err(|violation| violation.to_string())?;
+        print_standings(&standings, json_output);
+        if let Some(path) = stats_path {
+            let names = player_names_from_args(args, game.player_count)?;
+            let record = GameRecord::from_game(&game, &standings, &names, Some(scenario.seed));
+            append_record(&path, &record).map_err(|error| error.to_string())?;
+        }
+        return Ok(());
     }
 
-    /// Match cards in each player's hand
-    fn players_match_cards(&mut self) {
-        self.players.iter_mut().for_each(|player| {
-            let _pairs_found = player.match_pairs();
-        });
-    }
-
-    /// Get the index of the next player
-    /// If the player is the last player, return the first player
-    /// Otherwise, return the next player
-    ///
-    /// # Arguments
-    ///
-    /// * `player_index` - The index of the current player
-    ///
-    /// # Example
-    ///
-    /// ```
-    /// use rust_go_fish::GameState;
-    ///
-    /// let game = GameState::new(4);
-    /// let next_player_index = game.next_player_index(0);
-    /// assert_eq!(next_player_index, 1);
-    /// ```
-    fn next_player_index(&self, player_index: usize) -> usize {
-        (player_index + 1) % self.player_count
-    }
-
-    /// Play a turn for a player
-    fn play_turn(&mut self, player_index: usize) -> PlayerTurnResult {
-        println!("player {} turn", player_index);
-        // get random card from player's hand and select another player to ask
-        let (random_card_index, other_player_index) = self.get_turn_indices(player_index);
-        self.perform_turn(player_index, other_player_index, random_card_index)
+    let player_count: usize = number_of(args, "--players")?.unwrap_or(4);
+    if player_count < 2 {
+        return Err("--players needs at least 2 players".to_string());
     }
-
-    /// Get the indices of the card to play and the player to ask
-    /// based on the current game mode selection
-    fn get_turn_indices(&self, player_index: usize) -> (usize, usize) {
-        match self.game_mode {
-            GameMode::Random => {
-                let random_card_index = get_random(0..self.players[player_index].hand.len());
-                let other_player_index = get_random_excluding(0..self.player_count, player_index);
-                (random_card_index, other_player_index)
-            }
-            GameMode::Sequential => {
-                let random_card_index = 0;
-                let other_player_index = self.next_player_index(player_index);
-                (random_card_index, other_player_index)
-            }
+    if args.iter().any(|arg| arg == "--old-maid") {
+        if stats_path.is_some() {
+            return Err(
+                "--stats records Go Fish games, it can't be used with --old-maid".to_string(),
+            );
         }
-    }
-
-    /// Perform a turn for a player
-    fn perform_turn(
-        &mut self,
-        player_index: usize,
-        other_player_index: usize,
-        card_index: usize,
-    ) -> PlayerTurnResult {
-        // get random card from player's hand
-        let card = self.players[player_index].hand.cards()[card_index].clone();
-
-        // ask the other player for the card
-        match self.ask_for_card(player_index, other_player_index, card) {
-            AskCardResult::ReceiveCard(card) => {
-                println!("player {} received card: {}", player_index, card);
-                self.players[player_index].match_pairs();
-                PlayerTurnResult::PlayAgain
-            }
-            AskCardResult::GoFish => {
-                println!("player {} go fish", player_index);
-                match self.deck.draw(1) {
-                    Some(card) => {
-                        println!("player {} drew from deck", player_index);
-                        let player = &mut self.players[player_index];
-
-                        // add the card to the player's hand
-                        player.hand.append(&card);
-                        PlayerTurnResult::NextPlayerTurn
-                    }
-                    None => PlayerTurnResult::NextPlayerTurn,
-                }
-            }
+        let rng = match number_of(args, "--seed")? {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        };
+        let mut game = OldMaid::new(vec![GameMode::Random; player_count], rng);
+        game.game.verbose = !json_output;
+        let result = game.play();
+        if json_output {
+            println!("{}", result.to_json());
+        } else {
+            print!("{}", result);
         }
+        return Ok(());
     }
+    let rules = rules_from_args(args, player_count)?;
 
-    /// Check if the game should end
-    fn check_win_condition(&self) -> EndGameCondition {
-        // find any player with no cards in their hand
-        let empty_hands_index = self
-            .players
-            .iter()
-            .enumerate()
-            .find(|&(_, player)| player.hand.is_empty())
-            .map(|(index, _)| index);
-
-        // check to see if anyone no longer has cards in their hands
-        match empty_hands_index {
-            Some(index) => {
-                // determine a winner if any player has no more cards
-                println!("player {} has no more cards", index);
-                self.determine_winner()
-            }
-            None => {
-                // continue play
-                EndGameCondition::Continue
-            }
-        }
+    if args.get(1).map(String::as_str) == Some("play") {
+        let play_mode = if args.iter().any(|arg| arg == "--competitive") {
+            PlayMode::Competitive
+        } else {
+            PlayMode::Casual
+        };
+        return play_interactive(rules, player_count, play_mode);
     }
 
-    /// Determine the winner of the game
-    fn determine_winner(&self) -> EndGameCondition {
-        let winners = self.indices_of_max_score_players();
-        match winners.len() {
-            0 => EndGameCondition::Continue,
-            1 => EndGameCondition::Winner(winners[0]),
-            _ => EndGameCondition::Tie(winners),
+    if let Some(config) = tournament_from_args(args, &rules)? {
+        let result = Tournament::new(config).run();
+        if json_output {
+            println!("{}", result.to_json());
+        } else {
+            print!("{}", result);
         }
-    }
-
-    /// Get the indices of the players with the highest score
-    fn indices_of_max_score_players(&self) -> Vec<usize> {
-        if self.players.is_empty() {
-            return Vec::new();
+        if let Some(path) = ratings_path {
+            let games = result.games.iter().map(|game| {
+                let placements = placements_from_outcome(&game.outcome, game.seats.len());
+                (game.seats.clone(), placements)
+            });
+            record_ratings(&path, games)?;
         }
-
-        let max_score = self
-            .players
-            .iter()
-            .map(|player| player.score)
-            .max()
-            .unwrap(); // Safe to use unwrap as players is not empty
-
-        self.players
-            .iter()
-            .enumerate()
-            .filter_map(|(index, player)| {
-                if player.score == max_score {
-                    Some(index)
-                } else {
-                    None
-                }
-            })
-            .collect()
-    }
-
-    /// Transfer cards from one player to another
-    fn transfer_cards(&mut self, player_index: usize, other_player_index: usize, card: Card) {
-        // Logic for transferring cards
-        self.players[other_player_index].hand.remove_card(&card);
-        self.players[player_index].hand.push(card);
-    }
-
-    /// Ask another player for a card
-    /// If they have it, take it
-    /// If they don't, GoFish
-    fn ask_for_card(
-        &mut self,
-        player_index: usize,
-        answering_player_index: usize,
-        card: Card,
-    ) -> AskCardResult {
-        let result = self.players[answering_player_index].answer_for_card_rank(card.rank);
-        // check if they have the card
-        match result {
-            Some(card) => {
-                // take the card
-                self.transfer_cards(player_index, answering_player_index, card.clone());
-                AskCardResult::ReceiveCard(card)
-            }
-            None => AskCardResult::GoFish,
+        return Ok(());
+    }
+
+    let names = player_names_from_args(args, player_count)?;
+
+    if let Some(length) = match_length_from_args(args)? {
+        let mut config = MatchConfig::new(player_count, length);
+        config.game_mode = rules.game_mode;
+        config.rules = rules;
+        config.validate()?;
+        let mut game_match = Match::new(config);
+        game_match.verbose = !json_output;
+        let result = game_match.play();
+        if json_output {
+            println!("{}", result.to_json());
+        } else {
+            print!("{}", result);
+        }
+        if let Some(path) = ratings_path {
+            let games = result
+                .rounds
+                .iter()
+                .map(|round| (names.clone(), placements_from_standings(&round.standings)));
+            record_ratings(&path, games)?;
         }
+        return Ok(());
     }
-}
 
-fn main() {
     let environment = Environment::Development;
-    match environment {
+    let (standings, game, seed) = match environment {
         Environment::Development => {
             // Run
-            let mut game = GameState::new(4);
+            let seed = number_of(args, "--seed")?.unwrap_or_else(rand::random);
+            let game_modes = vec![rules.game_mode; player_count];
+            let mut game =
+                GameState::new_with_rules(game_modes, rules, StdRng::seed_from_u64(seed));
+            game.verbose = !json_output && view.is_none();
+            game.audit = args.iter().any(|arg| arg == "--audit");
             game.deal();
             game.players_match_cards();
-            let _game_result = run_game(&mut game);
+            let standings = match view {
+                Some(perspective) => {
+                    let first_player_index = game.first_player_index();
+                    watch_game(&mut game, first_player_index, perspective)
+                }
+                None => try_run_game(&mut game),
+            }
+            .map_err(|violation| violation.to_string())?;
+            (standings, game, Some(seed))
         }
         Environment::Test => {
             // Test
             let standard52 = Standard52::default();
-            let mut game = GameState::new_with_deck(4, standard52, GameMode::Sequential);
+            let mut game = GameState::new_with_deck(player_count, standard52, GameMode::Sequential);
+            let turn_limit = rules.turn_limit_for(&[GameMode::Sequential]);
+            game.rules = Rules {
+                turn_limit,
+                ..rules
+            };
+            game.verbose = !json_output;
             game.deal();
             game.players_match_cards();
-            let _game_result = run_game(&mut game);
+            (run_game(&mut game), game, None)
         }
         Environment::Production => {
             println!("Running in production mode");
             // Run
-            let mut game = GameState::new(4);
+            let game_modes = vec![GameMode::Random; player_count];
+            let mut game = GameState::new_with_rules(game_modes, rules, StdRng::from_entropy());
+            game.verbose = !json_output;
             game.deal();
             game.players_match_cards();
-            let _game_result = run_game(&mut game);
+            (run_game(&mut game), game, None)
         }
+    };
+
+    print_standings(&standings, json_output);
+    if let Some(path) = stats_path {
+        let record = GameRecord::from_game(&game, &standings, &names, seed);
+        append_record(&path, &record).map_err(|error| error.to_string())?;
+    }
+    if let Some(path) = ratings_path {
+        let placements = placements_from_standings(&standings);
+        record_ratings(&path, std::iter::once((names, placements)))?;
     }
+    Ok(())
 }
 
-/// Run the game
-fn run_game(game: &mut GameState) -> EndGameCondition {
-    let mut current_player_index = 0;
-    let mut end_game_condition = EndGameCondition::Continue;
-
-    // Game Loop
-    while end_game_condition == EndGameCondition::Continue {
-        let mut turn_result = PlayerTurnResult::PlayAgain;
-        while turn_result == PlayerTurnResult::PlayAgain {
-            // Update the outer 'turn_result' variable with the new turn result
-            turn_result = game.play_turn(current_player_index);
-            // println!("player_turn_result: {:?}", turn_result);
-
-            // Check the end game condition after each turn
-            end_game_condition = game.check_win_condition();
-            if end_game_condition != EndGameCondition::Continue {
-                break; // Break from the inner loop if the game should end
-            }
-        }
-        current_player_index = game.next_player_index(current_player_index);
-        // println!("current_player_index: {:?}", current_player_index);
-    }
+/// Get the value following a flag in the command line arguments
+fn value_of<'a>(args: &'a [String], flag: &str) -> Result<Option<&'a String>, String> {
+    args.iter()
+        .position(|arg| arg == flag)
+        .map(|position| {
+            args.get(position + 1)
+                .ok_or(format!("{} needs a value", flag))
+        })
+        .transpose()
+}
 
-    // println!("end_game_condition: {:?}", end_game_condition);
-    handle_end_game_condition(&end_game_condition, &game);
-    end_game_condition
+/// Get the number following a flag in the command line arguments
+fn number_of<T: std::str::FromStr>(args: &[String], flag: &str) -> Result<Option<T>, String> {
+    value_of(args, flag)?
+        .map(|value| {
+            value
+                .parse()
+                .map_err(|_| format!("{} needs a number", flag))
+        })
+        .transpose()
 }
 
-/// Handle the end game condition
-fn handle_end_game_condition(condition: &EndGameCondition, game_state: &GameState) {
-    match condition {
-        EndGameCondition::Winner(winner_index) => {
-            print_winner_info(winner_index, game_state);
-        }
-        EndGameCondition::Tie(tie_indices) => {
-            println!(
-                "There is a tie between players at indices {:?}",
-                tie_indices
-            );
-            for index in tie_indices {
-                print_winner_info(index, game_state);
-                println!();
+/// Build the game rules from the command line arguments
+///
+/// `--rules FILE` reads the rules from a TOML file and `--preset NAME` uses one of
+/// the rule sets shipped with the crate. The other flags change the rules read.
+/// `--turn-limit N` ends the game after N turns.
+///
+/// `--tie-break fewest-cards,highest-pair,first-out,shared` sets the tie breakers
+/// applied, in order, when several players share the highest score.
+///
+/// `--decks N` sets the number of decks, by default two for eight or more players
+/// and one otherwise. `--remove-ranks 2,3,4` takes ranks out of the deck and
+/// `--jokers` adds the jokers.
+///
+/// `--wild jokers|RANK` makes the jokers, which are then added to the deck, or a rank
+/// wild. `--surrender-wilds` makes a player without the asked rank hand over a wild
+/// card and `--wild-pair-score N` sets the score of a pair completed with a wild card.
+///
+/// `--happy-families` plays Happy Families with the classic families, `--families N`
+/// plays with the first N of them. The deck then holds the members of those families.
+fn rules_from_args(args: &[String], player_count: usize) -> Result<Rules, String> {
+    let mut rules = match (value_of(args, "--rules")?, value_of(args, "--preset")?) {
+        (Some(_), Some(_)) => return Err("use either --rules or --preset, not both".to_string()),
+        (Some(path), None) => Rules::load(Path::new(path)).map_err(|error| error.to_string())?,
+        (None, Some(name)) => Rules::preset(name).map_err(|error| error.to_string())?,
+        (None, None) => Rules {
+            deck: DeckConfig::for_players(player_count),
+            ..Rules::default()
+        },
+    };
+    if let Some(decks) = number_of(args, "--decks")? {
+        rules.deck.decks = decks;
+    }
+    if let Some(value) = value_of(args, "--remove-ranks")? {
+        rules.deck.removed_ranks = value
+            .split(',')
+            .map(|index| parse_rank(index.trim()).ok_or(format!("unknown rank: {}", index)))
+            .collect::<Result<Vec<_>, _>>()?;
+    }
+    if args.iter().any(|arg| arg == "--jokers") {
+        rules.deck.jokers = true;
+    }
+    if let Some(value) = value_of(args, "--wild")? {
+        let cards = match value.as_str() {
+            "jokers" => {
+                rules.deck.jokers = true;
+                WildCards::Jokers
             }
+            index => WildCards::Rank(parse_rank(index).ok_or(format!("unknown rank: {}", index))?),
+        };
+        let mut wild = WildRules::new(cards);
+        wild.surrender_when_asked = args.iter().any(|arg| arg == "--surrender-wilds");
+        if let Some(score) = number_of(args, "--wild-pair-score")? {
+            wild.wild_pair_score = score;
         }
-        EndGameCondition::Continue => {
-            // println!("The game continues");
-        }
+        rules.wild = Some(wild);
     }
+    if args.iter().any(|arg| arg == "--happy-families") {
+        if ["--decks", "--remove-ranks", "--jokers"]
+            .iter()
+            .any(|flag| args.iter().any(|arg| arg == flag))
+        {
+            return Err(
+                "--happy-families deals its own deck, leave out --decks, --remove-ranks and --jokers"
+                    .to_string(),
+            );
+        }
+        let families = match number_of(args, "--families")? {
+            Some(count @ 1..=13) => Families::classic(count),
+            Some(_) => return Err("--families needs between 1 and 13 families".to_string()),
+            None => Families::default(),
+        };
+        rules.deck = families.deck_config();
+        rules.families = Some(families);
+        rules.book_size = FAMILY_SIZE;
+    }
+    if let Some(value) = value_of(args, "--tie-break")? {
+        rules.tie_breakers = value
+            .split(',')
+            .map(|name| name.trim().parse::<TieBreaker>())
+            .collect::<Result<Vec<_>, _>>()?;
+    }
+    if let Some(turn_limit) = number_of(args, "--turn-limit")? {
+        rules.turn_limit = Some(turn_limit);
+    }
+    rules.validate().map_err(|error| error.to_string())?;
+    Ok(rules)
 }
 
-/// Print the winner's information
-fn print_winner_info(winner_index: &usize, game_state: &GameState) {
-    println!("The winner is player at index {}", winner_index);
-    println!(
-        "With a score of {}",
-        game_state.players[*winner_index].score
-    );
-    println!(
-        "With the following matching Pairs {}",
-        game_state.players[*winner_index].pairs.to_index()
-    );
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use cardpack::{JACK, TWO};
-
-    fn setup_random() -> GameState {
-        let mut game = GameState::new(4);
-        game.deal();
-        game.players_match_cards();
-        game
-    }
-
-    fn setup() -> GameState {
-        let standard52 = Standard52::default();
-        let mut game = GameState::new_with_deck(4, standard52, GameMode::Sequential);
-        game.deal();
-        game.players_match_cards();
-        game
-    }
-
-    #[test]
-    fn test_find_pair() {
-        let index_string = "2S 2D QS KH 3C 3S";
-        let pile = Standard52::pile_from_index(index_string).unwrap();
-        let result = find_pair(&pile).unwrap();
-
-        assert_eq!(
-            result,
-            (
-                Standard52::card_from_index("2S"),
-                Standard52::card_from_index("2D")
-            )
-        );
-    }
-
-    #[test]
-    fn test_player_answer_for_card_rank_in_hand() {
-        let index_string = "2S 2D QS KH 3C 3S";
-        let pile = Standard52::pile_from_index(index_string).unwrap();
-        let mut player = Player::new();
-        player.add_cards(pile);
-        let result = player.answer_for_card_rank(Rank::new(TWO)).unwrap();
-
-        // println!("result: {:?}", result);
-        assert_eq!(result, Standard52::card_from_index("2S"));
-    }
-
-    #[test]
-    fn test_player_answer_for_card_rank_not_in_hand() {
-        let index_string = "2S 2D QS KH 3C 3S";
-        let pile = Standard52::pile_from_index(index_string).unwrap();
-        let mut player = Player::new();
-        player.add_cards(pile);
-        let result = player.answer_for_card_rank(Rank::new(JACK));
-        assert_eq!(result, None);
+/// Read the match length from the command line arguments
+///
+/// `--rounds N` plays a match of N rounds and `--target-score N` plays rounds
+/// until a player's total score reaches N. Without either a single game is played.
+fn match_length_from_args(args: &[String]) -> Result<Option<MatchLength>, String> {
+    match (
+        number_of(args, "--rounds")?,
+        number_of(args, "--target-score")?,
+    ) {
+        (Some(_), Some(_)) => Err("use either --rounds or --target-score, not both".to_string()),
+        (Some(0), None) => Err("--rounds needs at least 1 round".to_string()),
+        (None, Some(0)) => Err("--target-score needs a target of at least 1".to_string()),
+        (Some(rounds), None) => Ok(Some(MatchLength::Rounds(rounds))),
+        (None, Some(target)) => Ok(Some(MatchLength::TargetScore(target))),
+        (None, None) => Ok(None),
     }
+}
 
-    #[test]
-    fn test_player_match_pairs() {
-        let index_string = "2S 2D QS KH 3C 3S";
-        let pile = Standard52::pile_from_index(index_string).unwrap();
-        let mut player = Player::new();
-        player.add_cards(pile);
-        let result = player.match_pairs();
-        assert_eq!(result, 2);
-        assert_eq!(player.hand.len(), 2);
-        assert_eq!(player.pairs.len(), 4);
-    }
+/// Read the tournament settings from the command line arguments
+///
+/// `--tournament round-robin|elimination` runs a tournament between the strategies
+/// listed with `--entrants` (default `random,sequential`). `--deals N` sets the deals
+/// played per pairing (default 100) and `--seed N` the seed of the first game (default 0).
+fn tournament_from_args(
+    args: &[String],
+    rules: &Rules,
+) -> Result<Option<TournamentConfig>, String> {
+    let pairing: Pairing = match value_of(args, "--tournament")? {
+        Some(value) => value.parse()?,
+        None => return Ok(None),
+    };
+    let entrants = value_of(args, "--entrants")?
+        .map(String::as_str)
+        .unwrap_or("random,sequential")
+        .split(',')
+        .enumerate()
+        .map(|(index, name)| {
+            let game_mode: GameMode = name.trim().parse()?;
+            Ok(Entrant::new(&format!("{}-{}", game_mode, index), game_mode))
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+    let config = TournamentConfig {
+        entrants,
+        pairing,
+        deals_per_pairing: number_of(args, "--deals")?.unwrap_or(100),
+        seed: number_of(args, "--seed")?.unwrap_or(0),
+        rules: rules.clone(),
+    };
+    config.validate()?;
+    Ok(Some(config))
+}
 
-    #[test]
-    fn test_setup_game() {
-        let game = setup();
-        // for player in &game.players {
-        //     println!("player: {:?}", &player.hand.to_index_str());
-        // }
-        assert_eq!(game.players.len(), 4);
-        assert_eq!(game.players[0].hand.len(), 7);
-
-        assert_eq!(game.players[0].hand.to_index_str(), "AS JH TS 7H 6S 3H 2S");
-        assert_eq!(game.players[1].hand.to_index_str(), "AH KS TH 9S 6H 5S 2H");
-        assert_eq!(game.players[2].hand.to_index_str(), "AD KH QS 9H 8S 5H 4S");
-        assert_eq!(game.players[3].hand.to_index_str(), "KD QH JS 8H 7S 4H 3S");
+/// Read the names of the players from `--names a,b,c,d`, defaulting to `player-N`
+fn player_names_from_args(args: &[String], player_count: usize) -> Result<Vec<String>, String> {
+    match value_of(args, "--names")? {
+        Some(value) => {
+            let names: Vec<String> = value
+                .split(',')
+                .map(|name| name.trim().to_string())
+                .collect();
+            if names.len() != player_count {
+                return Err(format!("--names needs {} names", player_count));
+            }
+            if let Some(position) =
+                (1..names.len()).find(|&index| names[..index].contains(&names[index]))
+            {
+                return Err(format!(
+                    "--names has {} twice, every player needs their own name",
+                    names[position]
+                ));
+            }
+            Ok(names)
+        }
+        None => Ok((0..player_count)
+            .map(|index| format!("player-{}", index))
+            .collect()),
     }
+}
 
-    #[test]
-    fn test_player_ask_for_card() {
-        let mut game = setup();
-        let card = Standard52::card_from_index("6S");
-        let current_player_index = 0;
-        let answering_player_index = 1;
-        let _player_turn_result =
-            game.ask_for_card(current_player_index, answering_player_index, card);
-        // println!("player_turn_result: {:?}", player_turn_result);
-        assert_eq!(game.players[current_player_index].hand.len(), 8);
-        game.players[current_player_index].match_pairs();
-        assert_eq!(game.players[current_player_index].pairs.len(), 2);
-    }
+/// Add the results of rated games to the rating file
+fn record_ratings(
+    path: &Path,
+    games: impl Iterator<Item = (Vec<String>, Vec<usize>)>,
+) -> Result<(), String> {
+    let mut store = RatingStore::load(path).map_err(|error| error.to_string())?;
+    for (names, placements) in games {
+        store
+            .record_game(&names, &placements)
+            .map_err(|error| error.to_string())?;
+    }
+    store.save(path).map_err(|error| error.to_string())
+}
 
-    #[test]
-    fn test_game_play_turn() {
-        let mut game = setup();
-        let current_player_index = 0;
-        let _player_turn_result = game.play_turn(current_player_index);
-        // println!("player_turn_result: {:?}", player_turn_result);
+/// Print the rating ladder
+fn show_ladder(path: &Path) -> Result<(), String> {
+    let store = RatingStore::load(path).map_err(|error| error.to_string())?;
+    print!("{}", store);
+    Ok(())
+}
 
-        assert_eq!(game.players[current_player_index].hand.len(), 6);
-        game.players[current_player_index].match_pairs();
-        assert_eq!(game.players[current_player_index].pairs.len(), 2);
+/// Print how a player's rating changed game by game
+fn show_history(path: &Path, name: &str) -> Result<(), String> {
+    let store = RatingStore::load(path).map_err(|error| error.to_string())?;
+    let player = store
+        .players
+        .get(name)
+        .ok_or(format!("{} has no rated games", name))?;
+    println!("{:>5}  {:>7}  {:>7}", "Game", "Rating", "Change");
+    for change in &player.history {
+        println!(
+            "{:>5}  {:>7.1}  {:>+7.1}",
+            change.game, change.rating, change.change
+        );
     }
+    Ok(())
+}
 
-    #[test]
-    fn test_run_game() {
-        let mut game = setup();
-        let result = run_game(&mut game);
-        match result {
-            EndGameCondition::Winner(winner_index) => {
-                assert_eq!(winner_index, 2);
-                assert_eq!(game.players[winner_index].score, 8);
-                assert_eq!(
-                    game.players[winner_index].pairs.to_index_str(),
-                    "AD AC QS QH 8S 8H 5H 5D 4S 4H 2D 2C JS JC 3S 3C"
+/// Play a game at the terminal, seated at the first seat against the engine's players
+///
+/// On their turn the person types `ask PLAYER RANK`, or a card index such as `7S`
+/// in Happy Families, `hint` for the recommended ask, `undo` to take back their
+/// last ask and the turns played after it, `redo` to play them again, or `quit`.
+fn play_interactive(rules: Rules, player_count: usize, play_mode: PlayMode) -> Result<(), String> {
+    let game_modes = vec![rules.game_mode; player_count];
+    let mut game = GameState::new_with_rules(game_modes, rules, StdRng::from_entropy());
+    game.deal();
+    game.players_match_cards();
+    let mut history = GameHistory::new(game, play_mode);
+    let mut rng = StdRng::from_entropy();
+    let mut lines = io::stdin().lock().lines();
+
+    while history.outcome() == EndGameCondition::Continue {
+        if !is_human_turn(&history) {
+            history
+                .play_turn()
+                .map_err(|violation| violation.to_string())?;
+            continue;
+        }
+        println!(
+            "your hand: {}",
+            history.game.players[HUMAN_INDEX]
+                .hand
+                .to_pile()
+                .sort_by_frequency()
+        );
+        print!("> ");
+        io::stdout().flush().map_err(|error| error.to_string())?;
+        let line = match lines.next() {
+            Some(line) => line.map_err(|error| error.to_string())?,
+            None => return Ok(()),
+        };
+        let words: Vec<&str> = line.split_whitespace().collect();
+        match words.as_slice() {
+            ["ask", player, index] => {
+                let ask = player
+                    .parse()
+                    .map_err(|_| format!("unknown player: {}", player))
+                    .and_then(|other| Ok((other, card_to_ask(&history.game, index)?)));
+                match ask {
+                    Ok((other, card)) => {
+                        if let Err(violation) = history.ask(other, card) {
+                            println!("{}", violation);
+                        }
+                    }
+                    Err(error) => println!("{}", error),
+                }
+            }
+            ["hint"] => {
+                let view = PlayerView::from_game(&history.game, HUMAN_INDEX);
+                let tracker = CardTracker::from_game(&history.game, Some(HUMAN_INDEX));
+                print!(
+                    "{}",
+                    hint(&view, &tracker, &SearchConfig::default(), &mut rng)
                 );
             }
-            EndGameCondition::Tie(_tie_indices) => {}
-            EndGameCondition::Continue => {}
+            ["undo"] => loop {
+                match history.undo() {
+                    Ok(events) => {
+                        for event in events {
+                            println!("undo: {}", Perspective::Seat(HUMAN_INDEX).describe(&event));
+                        }
+                    }
+                    Err(error) => {
+                        println!("{}", error);
+                        break;
+                    }
+                }
+                if is_human_turn(&history) {
+                    break;
+                }
+            },
+            ["redo"] => loop {
+                match history.redo() {
+                    Ok(events) => {
+                        for event in events {
+                            println!("redo: {}", Perspective::Seat(HUMAN_INDEX).describe(&event));
+                        }
+                    }
+                    Err(error) => {
+                        println!("{}", error);
+                        break;
+                    }
+                }
+                if is_human_turn(&history) || !history.can_redo() {
+                    break;
+                }
+            },
+            ["quit"] => return Ok(()),
+            _ => println!("commands: ask PLAYER RANK, hint, undo, redo, quit"),
         }
     }
 
-    #[test]
-    fn test_run_game_random() {
-        let mut game = setup_random();
-        let result = run_game(&mut game);
-        match &result {
-            EndGameCondition::Winner(winner_index) => {
-                println!("winner_index: {:?}", winner_index);
-            }
-            EndGameCondition::Tie(_tie_indices) => {}
-            EndGameCondition::Continue => {}
+    let outcome = history.outcome();
+    let tie_break = history.game.tie_break();
+    print_standings(
+        &Standings::from_game(outcome, tie_break, &history.game),
+        false,
+    );
+    Ok(())
+}
+
+/// Check if the person at the terminal is to choose an ask
+fn is_human_turn(history: &GameHistory) -> bool {
+    history.current_player == HUMAN_INDEX && !history.game.players[HUMAN_INDEX].hand.is_empty()
+}
+
+/// Play the rest of a game, printing every event as the perspective sees it
+/// and the table once the game is over
+fn watch_game(
+    game: &mut GameState,
+    player_index: usize,
+    perspective: Perspective,
+) -> Result<Standings, RuleViolation> {
+    let mut stream = SpectatorStream::new(perspective);
+    let print_lines = |stream: &mut SpectatorStream, game: &GameState| {
+        for line in stream.next_lines(game) {
+            println!("{}", line);
         }
-        assert_ne!(result, EndGameCondition::Continue);
-    }
+    };
+    print_lines(&mut stream, game);
+    let standings =
+        try_run_game_observed(game, player_index, |game| print_lines(&mut stream, game))?;
+    print!("{}", perspective.table(game));
+    Ok(standings)
+}
+
+/// Get the card to ask for from a rank, or from a card index in Happy Families
+fn card_to_ask(game: &GameState, index: &str) -> Result<Card, String> {
+    if game.rules.families.is_some() {
+        return parse_card(index).ok_or(format!("unknown card: {}", index));
+    }
+    let rank = parse_rank(index).ok_or(format!("unknown rank: {}", index))?;
+    game.players[HUMAN_INDEX]
+        .hand
+        .cards()
+        .iter()
+        .find(|card| card.rank == rank)
+        .cloned()
+        .ok_or(format!("you hold no card of rank {}", index))
+}
 
-    #[test]
-    fn test_game_next_player_index() {
-        let game = setup();
-        let next_player_index = game.next_player_index(0);
-        assert_eq!(next_player_index, 1);
+/// Print the final standings as a table or as JSON
+fn print_standings(standings: &Standings, json_output: bool) {
+    if json_output {
+        println!("{}", standings.to_json());
+    } else {
+        print!("{}", standings);
     }
 }
@@ -0,0 +1,272 @@
+use crate::deck::parse_card;
+use crate::game::{GameMode, GameState};
+use crate::get_random_from;
+use cardpack::{Card, Pile};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use serde::Serialize;
+use std::fmt;
+
+/// The queen taken out of the deck, leaving one queen without a partner
+pub const REMOVED_QUEEN: &str = "QC";
+
+/// Build the Old Maid deck, a standard deck without the queen of clubs
+pub fn old_maid_deck() -> Pile {
+    // REMOVED_QUEEN is a valid card index
+    let removed = parse_card(REMOVED_QUEEN).unwrap();
+    Pile::from_vector(
+        Pile::french_deck()
+            .cards()
+            .iter()
+            .filter(|&card| *card != removed)
+            .cloned()
+            .collect(),
+    )
+}
+
+/// The result of a game of Old Maid
+#[derive(Debug, PartialEq, Eq, Clone, Serialize)]
+pub struct OldMaidResult {
+    /// The player left holding the odd queen
+    pub loser: usize,
+    /// Indices of the players in the order they got rid of their cards
+    pub went_out: Vec<usize>,
+    /// Number of pairs each player discarded, by player index
+    pub pairs: Vec<usize>,
+    /// Number of cards drawn from a neighbour over the game
+    pub draws: u32,
+}
+
+impl OldMaidResult {
+    /// Serialize the result as pretty printed JSON
+    pub fn to_json(&self) -> String {
+        // Serializing plain structs and numbers can not fail
+        serde_json::to_string_pretty(self).unwrap()
+    }
+}
+
+impl fmt::Display for OldMaidResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "The old maid is player at index {}", self.loser)?;
+        writeln!(f, "{:>5}  {:>6}  {:>5}", "Place", "Player", "Pairs")?;
+        for (place, &player_index) in self.went_out.iter().chain([&self.loser]).enumerate() {
+            writeln!(
+                f,
+                "{:>5}  {:>6}  {:>5}",
+                place + 1,
+                player_index,
+                self.pairs[player_index]
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// A game of Old Maid played with the Go Fish players, piles and pairing
+///
+/// All cards are dealt and every player discards their pairs. In turn each
+/// player draws a card from the hand of the next player still holding cards
+/// and discards the pair it makes. Players without cards are out, and the
+/// last player holding a card is left with the odd queen and loses.
+///
+/// # Example
+///
+/// ```
+/// use rust_go_fish::old_maid::OldMaid;
+/// use rust_go_fish::GameMode;
+/// use rand::rngs::StdRng;
+/// use rand::SeedableRng;
+///
+/// let mut game = OldMaid::new(vec![GameMode::Random; 3], StdRng::seed_from_u64(1));
+/// game.game.verbose = false;
+/// let result = game.play();
+/// assert_eq!(result.went_out.len(), 2);
+/// ```
+pub struct OldMaid {
+    /// The shared game state, the stock is empty once the cards are dealt
+    pub game: GameState,
+    pub draws: u32,
+}
+
+impl OldMaid {
+    /// Create a game with one player for each game mode and a deck shuffled by the given rng
+    pub fn new(game_modes: Vec<GameMode>, mut rng: StdRng) -> Self {
+        let mut cards = old_maid_deck().cards().clone();
+        cards.shuffle(&mut rng);
+        OldMaid::new_with_deck(game_modes, Pile::from_vector(cards), rng)
+    }
+
+    /// Create a game with one player for each game mode dealing the given deck
+    pub fn new_with_deck(game_modes: Vec<GameMode>, deck: Pile, rng: StdRng) -> Self {
+        OldMaid {
+            game: GameState::new_with_modes(game_modes, deck, rng),
+            draws: 0,
+        }
+    }
+
+    /// Deal every card, starting with the player after the dealer, and discard the pairs
+    pub fn deal(&mut self) {
+        let first_player_index = self.game.first_player_index();
+        let mut player_index = first_player_index;
+        while let Some(card) = self.game.deck.draw_first() {
+            self.game.players[player_index].add_card(card);
+            player_index = self.game.next_player_index(player_index);
+        }
+        for offset in 0..self.game.player_count {
+            let player_index = (first_player_index + offset) % self.game.player_count;
+            self.game.players[player_index].match_pairs();
+            self.game.record_went_out(player_index);
+        }
+    }
+
+    /// Get the indices of the players still holding cards
+    pub fn players_in(&self) -> Vec<usize> {
+        (0..self.game.player_count)
+            .filter(|&index| !self.game.players[index].hand.is_empty())
+            .collect()
+    }
+
+    /// Get the next player after the given one who still holds cards
+    pub fn next_player_in(&self, player_index: usize) -> Option<usize> {
+        (1..self.game.player_count)
+            .map(|offset| (player_index + offset) % self.game.player_count)
+            .find(|&index| !self.game.players[index].hand.is_empty())
+    }
+
+    /// Get the position of the card a player draws from their neighbour's hand
+    /// based on the player's game mode
+    fn draw_position(&mut self, player_index: usize, neighbour_index: usize) -> usize {
+        match self.game.players[player_index].game_mode {
+            GameMode::Random | GameMode::Search => {
+                let hand_size = self.game.players[neighbour_index].hand.len();
+                get_random_from(&mut self.game.rng, 0..hand_size)
+            }
+            GameMode::Sequential => 0,
+        }
+    }
+
+    /// Draw the card at a position in the neighbour's hand and discard the pair it makes
+    pub fn draw_from(&mut self, player_index: usize, neighbour_index: usize, position: usize) {
+        let card: Card = self.game.players[neighbour_index].hand.remove(position);
+        if self.game.verbose {
+            println!(
+                "player {} drew {} from player {}",
+                player_index, card, neighbour_index
+            );
+        }
+        self.draws += 1;
+        self.game.players[player_index].add_card(card);
+        self.game.players[player_index].match_pairs();
+        self.game.record_went_out(neighbour_index);
+        self.game.record_went_out(player_index);
+    }
+
+    /// Play a turn for a player, drawing from the next player still holding cards
+    pub fn play_turn(&mut self, player_index: usize) {
+        if let Some(neighbour_index) = self.next_player_in(player_index) {
+            let position = self.draw_position(player_index, neighbour_index);
+            self.draw_from(player_index, neighbour_index, position);
+        }
+    }
+
+    /// Play until a single player is left holding cards
+    pub fn play(&mut self) -> OldMaidResult {
+        self.deal();
+        let mut current_player_index = self.game.first_player_index();
+        while self.players_in().len() > 1 {
+            if !self.game.players[current_player_index].hand.is_empty() {
+                self.play_turn(current_player_index);
+            }
+            current_player_index = self.game.next_player_index(current_player_index);
+        }
+        self.result()
+    }
+
+    /// Get the result of a finished game
+    pub fn result(&self) -> OldMaidResult {
+        let loser = self
+            .players_in()
+            .first()
+            .copied()
+            .unwrap_or(self.game.dealer_index);
+        OldMaidResult {
+            loser,
+            went_out: self.game.went_out.clone(),
+            pairs: self
+                .game
+                .players
+                .iter()
+                .map(|player| player.pairs.len() / 2)
+                .collect(),
+            draws: self.draws,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hand::Hand;
+    use cardpack::Standard52;
+    use rand::SeedableRng;
+
+    fn setup(game_modes: Vec<GameMode>, deck: Pile) -> OldMaid {
+        let mut game = OldMaid::new_with_deck(game_modes, deck, StdRng::seed_from_u64(0));
+        game.game.verbose = false;
+        game
+    }
+
+    #[test]
+    fn test_old_maid_deck_has_odd_queen() {
+        let deck = old_maid_deck();
+        assert_eq!(deck.len(), 51);
+        assert_eq!(
+            deck.map_by_rank_count()
+                .values()
+                .filter(|&&n| n == 3)
+                .count(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_deal_deals_every_card_and_discards_pairs() {
+        let deck = Standard52::pile_from_index("AS 5H AH 5D QS 9C").unwrap();
+        let mut game = setup(vec![GameMode::Sequential; 2], deck);
+        game.deal();
+
+        assert!(game.game.deck.is_empty());
+        assert_eq!(game.game.players[0].hand.to_index(), "QS");
+        assert_eq!(game.game.players[1].hand.to_index(), "9C");
+        assert_eq!(game.game.players[0].pairs.len(), 2);
+        assert_eq!(game.game.players[1].pairs.len(), 2);
+    }
+
+    #[test]
+    fn test_draw_from_neighbour_discards_pair() {
+        let mut game = setup(vec![GameMode::Sequential; 3], Pile::default());
+        game.game.players[0].hand = Standard52::pile_from_index("5C QS").unwrap().into();
+        game.game.players[1].hand = Hand::new();
+        game.game.players[2].hand = Standard52::pile_from_index("5H").unwrap().into();
+
+        assert_eq!(game.next_player_in(0), Some(2));
+        game.play_turn(0);
+
+        assert_eq!(game.game.players[0].hand.to_index(), "QS");
+        assert_eq!(game.game.went_out, vec![2]);
+        assert_eq!(game.players_in(), vec![0]);
+    }
+
+    #[test]
+    fn test_play_loser_holds_the_odd_queen() {
+        let mut game = OldMaid::new(vec![GameMode::Random; 4], StdRng::seed_from_u64(8));
+        game.game.verbose = false;
+        let result = game.play();
+
+        let loser_hand = game.game.players[result.loser].hand.to_index();
+        assert_eq!(loser_hand.len(), 2);
+        assert!(loser_hand.starts_with('Q'));
+        assert_eq!(result.went_out.len(), 3);
+        assert_eq!(result.pairs.iter().sum::<usize>(), 25);
+    }
+}
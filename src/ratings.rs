@@ -0,0 +1,311 @@
+use crate::game::EndGameCondition;
+use crate::standings::Standings;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Rating given to a player the first time they are seen
+pub const DEFAULT_RATING: f64 = 1500.0;
+
+/// Largest change in rating a single game can cause
+pub const K_FACTOR: f64 = 32.0;
+
+#[derive(Debug)]
+pub enum RatingError {
+    Io(io::Error),
+    Format(serde_json::Error),
+    /// The names and placements passed in do not describe the same players
+    PlayerMismatch,
+    /// A name appears more than once in the same game
+    DuplicatePlayer(String),
+}
+
+impl fmt::Display for RatingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RatingError::Io(error) => write!(f, "could not access the rating file: {}", error),
+            RatingError::Format(error) => write!(f, "the rating file is not valid: {}", error),
+            RatingError::PlayerMismatch => {
+                write!(f, "every player needs exactly one placement")
+            }
+            RatingError::DuplicatePlayer(name) => {
+                write!(f, "{} is named more than once in the same game", name)
+            }
+        }
+    }
+}
+
+impl std::error::Error for RatingError {}
+
+impl From<io::Error> for RatingError {
+    fn from(error: io::Error) -> Self {
+        RatingError::Io(error)
+    }
+}
+
+impl From<serde_json::Error> for RatingError {
+    fn from(error: serde_json::Error) -> Self {
+        RatingError::Format(error)
+    }
+}
+
+/// A player's rating after one rated game
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct RatingChange {
+    /// Number of the game in the rating store, starting at 1
+    pub game: u32,
+    pub rating: f64,
+    pub change: f64,
+}
+
+/// A player's or bot's current rating and how it got there
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct PlayerRating {
+    pub rating: f64,
+    pub games: u32,
+    pub history: Vec<RatingChange>,
+}
+
+impl Default for PlayerRating {
+    fn default() -> Self {
+        PlayerRating {
+            rating: DEFAULT_RATING,
+            games: 0,
+            history: Vec::new(),
+        }
+    }
+}
+
+/// Elo ratings for every player and bot that has played a rated game,
+/// stored as JSON in a local file
+///
+/// A game with several players is rated as if every player had played a
+/// heads up game against each of the others, won by the better placed player
+/// and drawn on equal placements.
+#[derive(Debug, PartialEq, Clone, Default, Serialize, Deserialize)]
+pub struct RatingStore {
+    pub games_recorded: u32,
+    pub players: BTreeMap<String, PlayerRating>,
+}
+
+impl RatingStore {
+    /// Load the ratings from a file, starting empty if the file does not exist yet
+    pub fn load(path: &Path) -> Result<Self, RatingError> {
+        match fs::read_to_string(path) {
+            Ok(contents) => Ok(serde_json::from_str(&contents)?),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(RatingStore::default()),
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    /// Save the ratings to a file
+    pub fn save(&self, path: &Path) -> Result<(), RatingError> {
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Get the current rating of a player
+    pub fn rating(&self, name: &str) -> f64 {
+        self.players
+            .get(name)
+            .map_or(DEFAULT_RATING, |player| player.rating)
+    }
+
+    /// Update the ratings after a game
+    ///
+    /// # Arguments
+    ///
+    /// * `names` - The name of the player in each seat
+    /// * `placements` - The finishing place of the player in each seat, 1 is best.
+    ///   Players with the same placement tied with each other.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rust_go_fish::ratings::{RatingStore, DEFAULT_RATING};
+    ///
+    /// let mut store = RatingStore::default();
+    /// let names = vec!["alice".to_string(), "bob".to_string()];
+    /// store.record_game(&names, &[1, 2]).unwrap();
+    /// assert!(store.rating("alice") > DEFAULT_RATING);
+    /// assert!(store.rating("bob") < DEFAULT_RATING);
+    /// ```
+    pub fn record_game(
+        &mut self,
+        names: &[String],
+        placements: &[usize],
+    ) -> Result<(), RatingError> {
+        if names.len() != placements.len() || names.len() < 2 {
+            return Err(RatingError::PlayerMismatch);
+        }
+        if let Some(position) = (1..names.len()).find(|&seat| names[..seat].contains(&names[seat]))
+        {
+            return Err(RatingError::DuplicatePlayer(names[position].clone()));
+        }
+
+        let ratings: Vec<f64> = names.iter().map(|name| self.rating(name)).collect();
+        let opponents = (names.len() - 1) as f64;
+        self.games_recorded += 1;
+
+        for (seat, name) in names.iter().enumerate() {
+            let mut actual = 0.0;
+            let mut expected = 0.0;
+            for other in 0..names.len() {
+                if other == seat {
+                    continue;
+                }
+                actual += match placements[seat].cmp(&placements[other]) {
+                    std::cmp::Ordering::Less => 1.0,
+                    std::cmp::Ordering::Equal => 0.5,
+                    std::cmp::Ordering::Greater => 0.0,
+                };
+                expected += 1.0 / (1.0 + 10f64.powf((ratings[other] - ratings[seat]) / 400.0));
+            }
+
+            let change = K_FACTOR * (actual - expected) / opponents;
+            let player = self.players.entry(name.clone()).or_default();
+            player.rating += change;
+            player.games += 1;
+            player.history.push(RatingChange {
+                game: self.games_recorded,
+                rating: player.rating,
+                change,
+            });
+        }
+        Ok(())
+    }
+
+    /// Get the players ordered from highest to lowest rating
+    pub fn ladder(&self) -> Vec<(&String, &PlayerRating)> {
+        let mut ladder: Vec<(&String, &PlayerRating)> = self.players.iter().collect();
+        ladder.sort_by(|a, b| b.1.rating.total_cmp(&a.1.rating));
+        ladder
+    }
+}
+
+/// Get the placement of each player from a game outcome.
+/// Winners and tied players come first, everybody else shares second place.
+pub fn placements_from_outcome(outcome: &EndGameCondition, player_count: usize) -> Vec<usize> {
+    (0..player_count)
+        .map(|index| match outcome {
+            EndGameCondition::Winner(winner) if *winner == index => 1,
+            EndGameCondition::Tie(tied) if tied.contains(&index) => 1,
+            EndGameCondition::Continue => 1,
+            _ => 2,
+        })
+        .collect()
+}
+
+/// Get the placement of each player, by player index, from the final standings.
+/// The standings already place a winner decided by a tie breaker ahead of the
+/// players it beat.
+pub fn placements_from_standings(standings: &Standings) -> Vec<usize> {
+    let mut placements = vec![0; standings.players.len()];
+    for standing in &standings.players {
+        placements[standing.player_index] = standing.rank;
+    }
+    placements
+}
+
+impl fmt::Display for RatingStore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name_width = self
+            .players
+            .keys()
+            .map(|name| name.len())
+            .max()
+            .unwrap_or(0)
+            .max(4);
+        writeln!(
+            f,
+            "{:>4}  {:<name_width$}  {:>7}  {:>5}",
+            "Rank", "Name", "Rating", "Games"
+        )?;
+        for (position, (name, player)) in self.ladder().into_iter().enumerate() {
+            writeln!(
+                f,
+                "{:>4}  {:<name_width$}  {:>7.1}  {:>5}",
+                position + 1,
+                name,
+                player.rating,
+                player.games
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn test_record_game_is_zero_sum() {
+        let mut store = RatingStore::default();
+        let players = names(&["a", "b", "c", "d"]);
+        store.record_game(&players, &[1, 2, 2, 4]).unwrap();
+        store.record_game(&players, &[3, 1, 2, 4]).unwrap();
+
+        let total: f64 = players.iter().map(|name| store.rating(name)).sum();
+        assert!((total - 4.0 * DEFAULT_RATING).abs() < 1e-9);
+        assert_eq!(store.players["a"].history.len(), 2);
+        assert_eq!(store.games_recorded, 2);
+    }
+
+    #[test]
+    fn test_record_game_tie_between_equals_changes_nothing() {
+        let mut store = RatingStore::default();
+        store.record_game(&names(&["a", "b"]), &[1, 1]).unwrap();
+        assert_eq!(store.rating("a"), DEFAULT_RATING);
+        assert_eq!(store.rating("b"), DEFAULT_RATING);
+    }
+
+    #[test]
+    fn test_record_game_player_mismatch() {
+        let mut store = RatingStore::default();
+        let result = store.record_game(&names(&["a", "b"]), &[1]);
+        assert!(matches!(result, Err(RatingError::PlayerMismatch)));
+    }
+
+    #[test]
+    fn test_record_game_duplicate_player() {
+        let mut store = RatingStore::default();
+        let result = store.record_game(&names(&["a", "b", "a"]), &[1, 2, 3]);
+        assert!(matches!(result, Err(RatingError::DuplicatePlayer(name)) if name == "a"));
+        assert_eq!(store.games_recorded, 0);
+        assert_eq!(store.rating("a"), DEFAULT_RATING);
+    }
+
+    #[test]
+    fn test_placements_from_outcome() {
+        assert_eq!(
+            placements_from_outcome(&EndGameCondition::Winner(2), 3),
+            vec![2, 2, 1]
+        );
+        assert_eq!(
+            placements_from_outcome(&EndGameCondition::Tie(vec![0, 1]), 3),
+            vec![1, 1, 2]
+        );
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let path =
+            std::env::temp_dir().join(format!("go_fish_ratings_{}.json", std::process::id()));
+        let mut store = RatingStore::default();
+        store.record_game(&names(&["a", "b"]), &[2, 1]).unwrap();
+        store.save(&path).unwrap();
+
+        let loaded = RatingStore::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(loaded, store);
+        assert_eq!(loaded.ladder()[0].0, "b");
+    }
+}
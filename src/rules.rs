@@ -0,0 +1,650 @@
+use crate::deck::{is_joker, parse_rank, DeckConfig, MAX_DECKS};
+use crate::families::{Families, CLASSIC_FAMILY_COUNT, FAMILY_SIZE};
+use crate::game::GameMode;
+use cardpack::{Card, Named, Rank};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+
+/// A way of choosing a single winner between players with the same score
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Serialize)]
+pub enum TieBreaker {
+    /// The tied player with the fewest cards left in hand wins
+    FewestCardsInHand,
+    /// The tied player holding the highest ranked pair wins
+    HighestRankedPair,
+    /// The tied player who ran out of cards first wins
+    FirstOut,
+    /// Stop breaking ties and let the tied players share the victory
+    SharedVictory,
+}
+
+impl fmt::Display for TieBreaker {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let reason = match self {
+            TieBreaker::FewestCardsInHand => "fewest cards left in hand",
+            TieBreaker::HighestRankedPair => "highest ranked pair",
+            TieBreaker::FirstOut => "first player out of cards",
+            TieBreaker::SharedVictory => "shared victory",
+        };
+        write!(f, "{}", reason)
+    }
+}
+
+impl FromStr for TieBreaker {
+    type Err = String;
+
+    /// Parse a tie breaker from its command line name
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rust_go_fish::rules::TieBreaker;
+    ///
+    /// let tie_breaker: TieBreaker = "fewest-cards".parse().unwrap();
+    /// assert_eq!(tie_breaker, TieBreaker::FewestCardsInHand);
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "fewest-cards" => Ok(TieBreaker::FewestCardsInHand),
+            "highest-pair" => Ok(TieBreaker::HighestRankedPair),
+            "first-out" => Ok(TieBreaker::FirstOut),
+            "shared" => Ok(TieBreaker::SharedVictory),
+            _ => Err(format!("unknown tie breaker: {}", s)),
+        }
+    }
+}
+
+/// The cards that stand in for any other card
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum WildCards {
+    /// The big and little jokers are wild, add them with `DeckConfig::jokers`
+    Jokers,
+    /// Every card of the rank is wild
+    Rank(Rank),
+}
+
+impl WildCards {
+    /// Check if a card is wild
+    pub fn is_wild(&self, card: &Card) -> bool {
+        match self {
+            WildCards::Jokers => is_joker(card),
+            WildCards::Rank(rank) => card.rank == *rank,
+        }
+    }
+}
+
+/// House rule where wild cards complete any pair
+///
+/// # Example
+///
+/// ```
+/// use rust_go_fish::deck::parse_rank;
+/// use rust_go_fish::rules::{WildCards, WildRules};
+///
+/// // twos are wild and must be handed over when no asked card is held
+/// let wild = WildRules {
+///     surrender_when_asked: true,
+///     ..WildRules::new(WildCards::Rank(parse_rank("2").unwrap()))
+/// };
+/// assert_eq!(wild.wild_pair_score, 1);
+/// ```
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct WildRules {
+    pub cards: WildCards,
+    /// A player asked for a rank they don't hold must hand over a wild card instead
+    pub surrender_when_asked: bool,
+    /// Score for a pair completed with a wild card, a natural pair scores one
+    pub wild_pair_score: u32,
+}
+
+impl WildRules {
+    /// Create wild card rules where wild pairs score the same as natural pairs
+    /// and wild cards are kept when asked for another rank
+    pub fn new(cards: WildCards) -> Self {
+        WildRules {
+            cards,
+            surrender_when_asked: false,
+            wild_pair_score: 1,
+        }
+    }
+}
+
+/// Number of cards dealt to every player unless the hand size table says otherwise
+pub const DEFAULT_HAND_SIZE: usize = 7;
+
+/// Most cards dealt to a player, a whole deck
+pub const MAX_HAND_SIZE: usize = 52;
+
+/// Turn limit a game with sequential players is played with when the rules have none
+pub const DEFAULT_SEQUENTIAL_TURN_LIMIT: u32 = 1000;
+
+/// The rule sets shipped with the crate, by name
+const PRESETS: [(&str, &str); 4] = [
+    ("standard", include_str!("../rules/standard.toml")),
+    ("books", include_str!("../rules/books.toml")),
+    (
+        "fish-your-wish",
+        include_str!("../rules/fish-your-wish.toml"),
+    ),
+    (
+        "happy-families",
+        include_str!("../rules/happy-families.toml"),
+    ),
+];
+
+#[derive(Debug)]
+pub enum RulesError {
+    Io(io::Error),
+    Parse(toml::de::Error),
+    /// The rules were read but describe a game that can not be played
+    Invalid(String),
+}
+
+impl fmt::Display for RulesError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RulesError::Io(error) => write!(f, "could not read the rules file: {}", error),
+            RulesError::Parse(error) => write!(f, "the rules file is not valid: {}", error),
+            RulesError::Invalid(reason) => write!(f, "the rules can not be played: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for RulesError {}
+
+impl From<io::Error> for RulesError {
+    fn from(error: io::Error) -> Self {
+        RulesError::Io(error)
+    }
+}
+
+impl From<toml::de::Error> for RulesError {
+    fn from(error: toml::de::Error) -> Self {
+        RulesError::Parse(error)
+    }
+}
+
+/// What happens when a player runs out of cards
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Serialize, Deserialize)]
+pub enum EmptyHand {
+    /// The game ends as soon as any player has no cards left
+    EndGame,
+    /// The player draws a card from the stock at the start of their turn,
+    /// or sits out if the stock is empty
+    Draw,
+    /// The player sits out for the rest of the game
+    SitOut,
+}
+
+impl FromStr for EmptyHand {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "end-game" => Ok(EmptyHand::EndGame),
+            "draw" => Ok(EmptyHand::Draw),
+            "sit-out" => Ok(EmptyHand::SitOut),
+            _ => Err(format!("unknown empty hand rule: {}", s)),
+        }
+    }
+}
+
+/// Cards dealt to each player from a number of players up
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct HandSize {
+    pub players: usize,
+    pub cards: usize,
+}
+
+/// The house rules a game is played with
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Rules {
+    /// Tie breakers applied in order when several players share the highest score.
+    /// If none of them separates the players the game ends in a tie.
+    pub tie_breakers: Vec<TieBreaker>,
+    /// The cards the game is played with
+    pub deck: DeckConfig,
+    /// Wild cards that complete any pair, if played with wild cards
+    pub wild: Option<WildRules>,
+    /// Play Happy Families with these families instead of Go Fish. Players ask for an
+    /// exact member of a family and lay down complete families instead of pairs.
+    /// Wild cards are not used in Happy Families.
+    pub families: Option<Families>,
+    /// Number of cards of a rank laid down together, two for pairs and four for books
+    pub book_size: usize,
+    /// Cards dealt to every player when the hand size table has no entry for the table
+    pub hand_size: usize,
+    /// Cards dealt by number of players, the entry with the most players
+    /// not above the number at the table is used
+    pub hand_sizes: Vec<HandSize>,
+    pub empty_hand: EmptyHand,
+    /// A player asked for a rank hands over every card of it instead of one
+    pub surrender_all: bool,
+    /// A player who draws the rank they asked for takes another turn
+    pub fish_your_wish: bool,
+    /// End the game and count the scores after this many turns
+    pub turn_limit: Option<u32>,
+    /// How the players pick their asks when no game modes are given
+    pub game_mode: GameMode,
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Rules {
+            tie_breakers: Vec::new(),
+            deck: DeckConfig::default(),
+            wild: None,
+            families: None,
+            book_size: 2,
+            hand_size: DEFAULT_HAND_SIZE,
+            hand_sizes: Vec::new(),
+            empty_hand: EmptyHand::EndGame,
+            surrender_all: false,
+            fish_your_wish: false,
+            turn_limit: None,
+            game_mode: GameMode::Random,
+        }
+    }
+}
+
+/// The rules as written in a TOML rules file, every key is optional
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct RulesFile {
+    book_size: Option<usize>,
+    hand_size: Option<usize>,
+    #[serde(default)]
+    hand_sizes: Vec<HandSize>,
+    empty_hand: Option<String>,
+    surrender_all: Option<bool>,
+    fish_your_wish: Option<bool>,
+    #[serde(default)]
+    tie_breakers: Vec<String>,
+    turn_limit: Option<u32>,
+    game_mode: Option<String>,
+    deck: Option<DeckFile>,
+    wild: Option<WildFile>,
+    happy_families: Option<usize>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct DeckFile {
+    decks: Option<usize>,
+    #[serde(default)]
+    removed_ranks: Vec<String>,
+    jokers: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct WildFile {
+    cards: String,
+    surrender_when_asked: Option<bool>,
+    wild_pair_score: Option<u32>,
+}
+
+impl Rules {
+    /// Get the number of cards dealt to every player at a table of the given size
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rust_go_fish::rules::{HandSize, Rules};
+    ///
+    /// let rules = Rules {
+    ///     hand_sizes: vec![HandSize { players: 4, cards: 5 }],
+    ///     ..Rules::default()
+    /// };
+    /// assert_eq!(rules.hand_size_for(3), 7);
+    /// assert_eq!(rules.hand_size_for(6), 5);
+    /// ```
+    pub fn hand_size_for(&self, player_count: usize) -> usize {
+        self.hand_sizes
+            .iter()
+            .filter(|entry| entry.players <= player_count)
+            .max_by_key(|entry| entry.players)
+            .map_or(self.hand_size, |entry| entry.cards)
+    }
+
+    /// Get the turn limit for a game between players with the given game modes
+    ///
+    /// Sequential players can ask each other in circles once the stock is empty, so a
+    /// table with one of them is limited to `DEFAULT_SEQUENTIAL_TURN_LIMIT` turns when
+    /// the rules set no limit.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rust_go_fish::rules::{Rules, DEFAULT_SEQUENTIAL_TURN_LIMIT};
+    /// use rust_go_fish::GameMode;
+    ///
+    /// let rules = Rules::default();
+    /// assert_eq!(rules.turn_limit_for(&[GameMode::Random; 2]), None);
+    /// assert_eq!(
+    ///     rules.turn_limit_for(&[GameMode::Random, GameMode::Sequential]),
+    ///     Some(DEFAULT_SEQUENTIAL_TURN_LIMIT)
+    /// );
+    /// ```
+    pub fn turn_limit_for(&self, game_modes: &[GameMode]) -> Option<u32> {
+        if game_modes.contains(&GameMode::Sequential) {
+            self.turn_limit.or(Some(DEFAULT_SEQUENTIAL_TURN_LIMIT))
+        } else {
+            self.turn_limit
+        }
+    }
+
+    /// Read the rules from a TOML file and check they can be played
+    pub fn load(path: &Path) -> Result<Self, RulesError> {
+        Rules::from_toml(&fs::read_to_string(path)?)
+    }
+
+    /// Get one of the rule sets shipped with the crate by name
+    pub fn preset(name: &str) -> Result<Self, RulesError> {
+        let (_, text) =
+            PRESETS
+                .iter()
+                .find(|(preset, _)| *preset == name)
+                .ok_or(RulesError::Invalid(format!(
+                    "unknown preset {}, the presets are {}",
+                    name,
+                    Rules::preset_names().join(", ")
+                )))?;
+        Rules::from_toml(text)
+    }
+
+    /// Get the names of the rule sets shipped with the crate
+    pub fn preset_names() -> Vec<&'static str> {
+        PRESETS.iter().map(|(name, _)| *name).collect()
+    }
+
+    /// Parse rules from TOML and check they can be played
+    ///
+    /// Keys left out keep their default value.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rust_go_fish::rules::{EmptyHand, Rules};
+    ///
+    /// let rules = Rules::from_toml("book_size = 4\nempty_hand = \"draw\"").unwrap();
+    /// assert_eq!(rules.book_size, 4);
+    /// assert_eq!(rules.empty_hand, EmptyHand::Draw);
+    ///
+    /// assert!(Rules::from_toml("book_size = 5").is_err());
+    /// ```
+    pub fn from_toml(text: &str) -> Result<Self, RulesError> {
+        Rules::from_file(toml::from_str(text)?)
+    }
+
+    /// Build the rules from a parsed rules file and check they can be played
+    pub(crate) fn from_file(file: RulesFile) -> Result<Self, RulesError> {
+        let mut rules = Rules::default();
+        if let Some(deck) = &file.deck {
+            rules.deck.decks = deck.decks.unwrap_or(rules.deck.decks);
+            rules.deck.jokers = deck.jokers.unwrap_or(rules.deck.jokers);
+            rules.deck.removed_ranks = deck
+                .removed_ranks
+                .iter()
+                .map(|index| {
+                    parse_rank(index).ok_or(RulesError::Invalid(format!("unknown rank: {}", index)))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+        }
+        if let Some(count) = file.happy_families {
+            if !(1..=CLASSIC_FAMILY_COUNT).contains(&count) {
+                return Err(RulesError::Invalid(format!(
+                    "happy_families needs between 1 and {} families",
+                    CLASSIC_FAMILY_COUNT
+                )));
+            }
+            let families = Families::classic(count);
+            // a [deck] table is kept, so validate can report it conflicting
+            if file.deck.is_none() {
+                rules.deck = families.deck_config();
+            }
+            rules.families = Some(families);
+            rules.book_size = FAMILY_SIZE;
+        }
+        rules.book_size = file.book_size.unwrap_or(rules.book_size);
+        rules.hand_size = file.hand_size.unwrap_or(rules.hand_size);
+        rules.hand_sizes = file.hand_sizes;
+        if let Some(empty_hand) = file.empty_hand {
+            rules.empty_hand = empty_hand.parse().map_err(RulesError::Invalid)?;
+        }
+        rules.surrender_all = file.surrender_all.unwrap_or(rules.surrender_all);
+        rules.fish_your_wish = file.fish_your_wish.unwrap_or(rules.fish_your_wish);
+        rules.tie_breakers = file
+            .tie_breakers
+            .iter()
+            .map(|name| name.parse())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(RulesError::Invalid)?;
+        rules.turn_limit = file.turn_limit;
+        if let Some(game_mode) = file.game_mode {
+            rules.game_mode = game_mode.parse().map_err(RulesError::Invalid)?;
+        }
+        if let Some(wild) = file.wild {
+            let cards = match wild.cards.as_str() {
+                "jokers" => WildCards::Jokers,
+                index => WildCards::Rank(
+                    parse_rank(index)
+                        .ok_or(RulesError::Invalid(format!("unknown wild rank: {}", index)))?,
+                ),
+            };
+            let defaults = WildRules::new(cards);
+            rules.wild = Some(WildRules {
+                surrender_when_asked: wild
+                    .surrender_when_asked
+                    .unwrap_or(defaults.surrender_when_asked),
+                wild_pair_score: wild.wild_pair_score.unwrap_or(defaults.wild_pair_score),
+                ..defaults
+            });
+        }
+        rules.validate()?;
+        Ok(rules)
+    }
+
+    /// Check the rules describe a game that can be played
+    pub fn validate(&self) -> Result<(), RulesError> {
+        let invalid = |reason: String| Err(RulesError::Invalid(reason));
+        if self.deck.decks == 0 || self.deck.decks > MAX_DECKS {
+            return invalid(format!(
+                "the game is played with between 1 and {} decks",
+                MAX_DECKS
+            ));
+        }
+        let deck_size = self.deck.build().len();
+        if deck_size == 0 {
+            return invalid("every rank was removed from the deck".to_string());
+        }
+        let cards_per_rank = 4 * self.deck.decks;
+        if self.book_size < 2 || self.book_size > cards_per_rank {
+            return invalid(format!(
+                "book_size must be between 2 and {}, the cards of a rank in the deck",
+                cards_per_rank
+            ));
+        }
+        if self.families.is_some() && self.book_size != FAMILY_SIZE {
+            return invalid(format!(
+                "Happy Families lays down families of {} cards",
+                FAMILY_SIZE
+            ));
+        }
+        if let Some(families) = &self.families {
+            if self.deck != families.deck_config() {
+                return invalid(format!(
+                    "Happy Families is played with the {} cards of its families, \
+                     it can't be given other deck settings",
+                    families.deck_config().build().len()
+                ));
+            }
+        }
+        if self.wild.is_some() && (self.book_size != 2 || self.families.is_some()) {
+            return invalid("wild cards only complete pairs".to_string());
+        }
+        match self.wild.as_ref().map(|wild| wild.cards) {
+            Some(WildCards::Jokers) if !self.deck.jokers => {
+                return invalid("wild jokers need a deck with jokers = true".to_string());
+            }
+            Some(WildCards::Rank(rank)) if self.deck.removed_ranks.contains(&rank) => {
+                return invalid(format!(
+                    "the wild rank {} is removed from the deck",
+                    rank.name.index_default()
+                ));
+            }
+            _ => {}
+        }
+        if self.hand_size > MAX_HAND_SIZE {
+            return invalid(format!("hand_size must be at most {}", MAX_HAND_SIZE));
+        }
+        if self.hand_size == 0 || self.hand_size * 2 > deck_size {
+            return invalid(format!(
+                "hand_size {} does not fit two players and a deck of {} cards",
+                self.hand_size, deck_size
+            ));
+        }
+        for (position, entry) in self.hand_sizes.iter().enumerate() {
+            if entry.players < 2 {
+                return invalid("hand_sizes entries need at least 2 players".to_string());
+            }
+            if entry.cards > MAX_HAND_SIZE {
+                return invalid(format!("hand_sizes deal at most {} cards", MAX_HAND_SIZE));
+            }
+            if entry.cards == 0 || entry.cards * entry.players > deck_size {
+                return invalid(format!(
+                    "dealing {} cards to {} players needs more than a deck of {} cards",
+                    entry.cards, entry.players, deck_size
+                ));
+            }
+            if self.hand_sizes[..position]
+                .iter()
+                .any(|other| other.players == entry.players)
+            {
+                return invalid(format!(
+                    "hand_sizes has two entries for {} players",
+                    entry.players
+                ));
+            }
+        }
+        if self.turn_limit == Some(0) {
+            return invalid("turn_limit must be at least 1".to_string());
+        }
+        if self.game_mode == GameMode::Sequential && self.turn_limit.is_none() {
+            return invalid(
+                "sequential players need a turn_limit, they can ask each other in circles \
+                 once the stock is empty"
+                    .to_string(),
+            );
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tie_breaker_from_str() {
+        assert_eq!("first-out".parse(), Ok(TieBreaker::FirstOut));
+        assert_eq!("shared".parse(), Ok(TieBreaker::SharedVictory));
+        assert!("coin-flip".parse::<TieBreaker>().is_err());
+    }
+
+    #[test]
+    fn test_presets_are_valid() {
+        for name in Rules::preset_names() {
+            assert!(Rules::preset(name).is_ok(), "preset {} is not valid", name);
+        }
+        assert!(Rules::preset("canasta").is_err());
+    }
+
+    #[test]
+    fn test_standard_preset_is_the_default() {
+        assert_eq!(Rules::preset("standard").unwrap(), Rules::default());
+    }
+
+    #[test]
+    fn test_from_toml_full() {
+        let text = r#"
+            book_size = 2
+            hand_size = 6
+            hand_sizes = [{ players = 5, cards = 5 }]
+            empty_hand = "sit-out"
+            surrender_all = true
+            fish_your_wish = true
+            tie_breakers = ["fewest-cards", "first-out"]
+            turn_limit = 300
+            game_mode = "sequential"
+
+            [deck]
+            decks = 2
+            removed_ranks = ["2", "3"]
+            jokers = true
+
+            [wild]
+            cards = "jokers"
+            wild_pair_score = 2
+        "#;
+        let rules = Rules::from_toml(text).unwrap();
+
+        assert_eq!(rules.hand_size_for(4), 6);
+        assert_eq!(rules.hand_size_for(5), 5);
+        assert_eq!(rules.empty_hand, EmptyHand::SitOut);
+        assert!(rules.surrender_all && rules.fish_your_wish);
+        assert_eq!(
+            rules.tie_breakers,
+            vec![TieBreaker::FewestCardsInHand, TieBreaker::FirstOut]
+        );
+        assert_eq!(rules.turn_limit, Some(300));
+        assert_eq!(rules.game_mode, GameMode::Sequential);
+        assert_eq!(rules.deck.build().len(), 2 * (44 + 2));
+        assert_eq!(rules.wild.unwrap().wild_pair_score, 2);
+    }
+
+    #[test]
+    fn test_from_toml_errors() {
+        let invalid = |text: &str| match Rules::from_toml(text) {
+            Err(RulesError::Invalid(reason)) => reason,
+            other => panic!("expected invalid rules, got {:?}", other),
+        };
+        assert!(invalid("book_size = 1").contains("book_size"));
+        assert!(invalid("hand_sizes = [{ players = 8, cards = 7 }]").contains("8 players"));
+        assert!(invalid("empty_hand = \"win\"").contains("win"));
+        assert!(invalid("tie_breakers = [\"coin-flip\"]").contains("coin-flip"));
+        assert!(invalid("book_size = 4\n[wild]\ncards = \"2\"").contains("wild"));
+        assert!(invalid("happy_families = 6\nbook_size = 2").contains("Happy Families"));
+        assert!(invalid("game_mode = \"sequential\"").contains("turn_limit"));
+        assert!(invalid("happy_families = 6\n[deck]\ndecks = 2").contains("Happy Families"));
+        assert!(invalid("[wild]\ncards = \"jokers\"").contains("jokers = true"));
+        assert!(
+            invalid("[deck]\nremoved_ranks = [\"2\"]\n[wild]\ncards = \"2\"").contains("wild rank")
+        );
+        assert!(invalid("[deck]\ndecks = 130").contains("decks"));
+        assert!(invalid("hand_size = 260\n[deck]\ndecks = 8").contains("hand_size"));
+        assert!(matches!(
+            Rules::from_toml("hand_size = \"seven\""),
+            Err(RulesError::Parse(_))
+        ));
+        assert!(matches!(
+            Rules::from_toml("decks = 2"),
+            Err(RulesError::Parse(_))
+        ));
+    }
+
+    #[test]
+    fn test_wild_cards_is_wild() {
+        use cardpack::Standard52;
+
+        let twos = WildCards::Rank(crate::deck::parse_rank("2").unwrap());
+        assert!(twos.is_wild(&Standard52::card_from_index("2H")));
+        assert!(!twos.is_wild(&Standard52::card_from_index("3H")));
+        assert!(!WildCards::Jokers.is_wild(&Standard52::card_from_index("2H")));
+    }
+}
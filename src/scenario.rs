@@ -0,0 +1,417 @@
+use crate::audit::{check_invariants, RuleViolation};
+use crate::deck::parse_pile;
+use crate::game::{GameEvent, GameMode, GameState, Player};
+use crate::rules::{Rules, RulesError, RulesFile};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use serde::Deserialize;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+#[derive(Debug)]
+pub enum ScenarioError {
+    Io(io::Error),
+    Parse(toml::de::Error),
+    Rules(RulesError),
+    /// The scenario was read but does not describe a table that can play
+    Invalid(String),
+    /// The cards of the scenario do not add up to the deck of its rules
+    Violation(RuleViolation),
+}
+
+impl fmt::Display for ScenarioError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScenarioError::Io(error) => write!(f, "could not read the scenario file: {}", error),
+            ScenarioError::Parse(error) => write!(f, "the scenario file is not valid: {}", error),
+            ScenarioError::Rules(error) => write!(f, "{}", error),
+            ScenarioError::Invalid(reason) => {
+                write!(f, "the scenario can not be played: {}", reason)
+            }
+            ScenarioError::Violation(violation) => {
+                write!(f, "the scenario can not be played: {}", violation)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ScenarioError {}
+
+impl From<io::Error> for ScenarioError {
+    fn from(error: io::Error) -> Self {
+        ScenarioError::Io(error)
+    }
+}
+
+impl From<toml::de::Error> for ScenarioError {
+    fn from(error: toml::de::Error) -> Self {
+        ScenarioError::Parse(error)
+    }
+}
+
+impl From<RulesError> for ScenarioError {
+    fn from(error: RulesError) -> Self {
+        ScenarioError::Rules(error)
+    }
+}
+
+/// A seat at the table of a scenario, with its cards written as index strings
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ScenarioPlayer {
+    pub game_mode: GameMode,
+    /// The cards in the player's hand, such as `"2S 2D QS"`
+    pub hand: String,
+    /// The cards the player has laid down
+    pub pairs: String,
+    /// The player's score, the number of books laid down if not given
+    pub score: Option<u32>,
+}
+
+/// A game in progress set up card by card instead of dealt, for tests and puzzles
+///
+/// Every card is written as an index string. The stock is listed from the top
+/// card down; when it is left out it holds the rest of the deck described by
+/// the rules, in deck order. Building the game checks that the hands, laid
+/// down cards and stock add up to exactly that deck.
+///
+/// # Example
+///
+/// ```
+/// use rust_go_fish::scenario::Scenario;
+/// use rust_go_fish::{run_game, EndGameCondition, GameMode, Rules};
+///
+/// // a short deck of aces and kings
+/// let rules = Rules::from_toml(r#"
+/// hand_size = 2
+/// [deck]
+/// removed_ranks = ["2", "3", "4", "5", "6", "7", "8", "9", "T", "J", "Q"]
+/// "#).unwrap();
+/// let mut game = Scenario::new(rules)
+///     .player(GameMode::Sequential, "AS KS")
+///     .player(GameMode::Sequential, "AH KH")
+///     .stock("AD KD AC KC")
+///     .current_player(1)
+///     .build()
+///     .unwrap();
+/// assert_eq!(game.first_player_index(), 1);
+///
+/// let standings = run_game(&mut game);
+/// assert_eq!(standings.outcome, EndGameCondition::Winner(1));
+/// ```
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Scenario {
+    pub rules: Rules,
+    pub players: Vec<ScenarioPlayer>,
+    /// The stock from the top card down, the rest of the deck if not given
+    pub stock: Option<String>,
+    /// The player whose turn it is
+    pub current_player: usize,
+    /// Seed for the random choices made during play
+    pub seed: u64,
+    /// Number of turns already played, counted towards the turn limit
+    pub turns: u32,
+}
+
+/// A scenario as written in a TOML file
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ScenarioFile {
+    preset: Option<String>,
+    rules: Option<RulesFile>,
+    current_player: Option<usize>,
+    seed: Option<u64>,
+    turns: Option<u32>,
+    stock: Option<String>,
+    players: Vec<PlayerFile>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PlayerFile {
+    game_mode: Option<String>,
+    hand: String,
+    #[serde(default)]
+    pairs: String,
+    score: Option<u32>,
+}
+
+impl Scenario {
+    /// Create a scenario without players, where the first player is about to ask
+    pub fn new(rules: Rules) -> Self {
+        Scenario {
+            rules,
+            players: Vec::new(),
+            stock: None,
+            current_player: 0,
+            seed: 0,
+            turns: 0,
+        }
+    }
+
+    /// Add a player holding the given cards and nothing laid down
+    pub fn player(mut self, game_mode: GameMode, hand: &str) -> Self {
+        self.players.push(ScenarioPlayer {
+            game_mode,
+            hand: hand.to_string(),
+            pairs: String::new(),
+            score: None,
+        });
+        self
+    }
+
+    /// Set the stock, from the top card down
+    pub fn stock(mut self, stock: &str) -> Self {
+        self.stock = Some(stock.to_string());
+        self
+    }
+
+    /// Set the player whose turn it is
+    pub fn current_player(mut self, player_index: usize) -> Self {
+        self.current_player = player_index;
+        self
+    }
+
+    /// Read a scenario from a TOML file
+    pub fn load(path: &Path) -> Result<Self, ScenarioError> {
+        Scenario::from_toml(&fs::read_to_string(path)?)
+    }
+
+    /// Parse a scenario from TOML
+    ///
+    /// The rules are either the name of a preset or a `[rules]` table with the
+    /// keys of a rules file. Every player is a `[[players]]` table with a hand,
+    /// and optionally a game mode, laid down cards and a score.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rust_go_fish::scenario::Scenario;
+    ///
+    /// let scenario = Scenario::from_toml(r#"
+    /// preset = "books"
+    /// current_player = 1
+    ///
+    /// [[players]]
+    /// hand = "AS AH AD"
+    ///
+    /// [[players]]
+    /// game_mode = "sequential"
+    /// hand = "AC 2C"
+    /// "#).unwrap();
+    /// assert_eq!(scenario.rules.book_size, 4);
+    ///
+    /// let game = scenario.build().unwrap();
+    /// assert_eq!(game.deck.len(), 52 - 5);
+    /// ```
+    pub fn from_toml(text: &str) -> Result<Self, ScenarioError> {
+        let file: ScenarioFile = toml::from_str(text)?;
+        let rules = match (file.preset, file.rules) {
+            (Some(_), Some(_)) => {
+                return Err(ScenarioError::Invalid(
+                    "use either a preset or a rules table, not both".to_string(),
+                ))
+            }
+            (Some(name), None) => Rules::preset(&name)?,
+            (None, Some(rules)) => Rules::from_file(rules)?,
+            (None, None) => Rules::default(),
+        };
+        let players = file
+            .players
+            .into_iter()
+            .map(|player| {
+                let game_mode = match player.game_mode {
+                    Some(game_mode) => game_mode.parse().map_err(ScenarioError::Invalid)?,
+                    None => rules.game_mode,
+                };
+                Ok(ScenarioPlayer {
+                    game_mode,
+                    hand: player.hand,
+                    pairs: player.pairs,
+                    score: player.score,
+                })
+            })
+            .collect::<Result<Vec<_>, ScenarioError>>()?;
+        Ok(Scenario {
+            rules,
+            players,
+            stock: file.stock,
+            current_player: file.current_player.unwrap_or(0),
+            seed: file.seed.unwrap_or(0),
+            turns: file.turns.unwrap_or(0),
+        })
+    }
+
+    /// Set up the game, with the current player about to take the first turn
+    ///
+    /// The narration of the game is turned off and no cards are matched, so
+    /// hands holding complete books keep them until the player's next turn.
+    /// The events of the game start with every player dealt their hand and laid
+    /// down cards, and laying the laid down cards down, so the transcript and a
+    /// card tracker begin from the scenario.
+    pub fn build(&self) -> Result<GameState, ScenarioError> {
+        let player_count = self.players.len();
+        if player_count < 2 {
+            return Err(ScenarioError::Invalid(
+                "a scenario needs at least 2 players".to_string(),
+            ));
+        }
+        if self.current_player >= player_count {
+            return Err(ScenarioError::Invalid(format!(
+                "there is no player at index {}",
+                self.current_player
+            )));
+        }
+
+        let mut players = Vec::new();
+        for seat in &self.players {
+            let mut player = Player::new_with_mode(seat.game_mode);
+            player.hand = parse_pile(&seat.hand)
+                .map_err(ScenarioError::Invalid)?
+                .into();
+            player.pairs = parse_pile(&seat.pairs).map_err(ScenarioError::Invalid)?;
+            player.score = seat
+                .score
+                .unwrap_or((player.pairs.len() / self.rules.book_size) as u32);
+            players.push(player);
+        }
+        let stock = match &self.stock {
+            Some(stock) => parse_pile(stock).map_err(ScenarioError::Invalid)?,
+            None => {
+                // cards held twice or not in the deck stay out of the stock
+                // and are reported by the invariant check below
+                let mut rest = self.rules.deck.build();
+                for player in &players {
+                    for card in player.hand.cards().iter().chain(player.pairs.cards()) {
+                        rest.remove_card(card);
+                    }
+                }
+                rest
+            }
+        };
+
+        let game_modes: Vec<GameMode> = self.players.iter().map(|seat| seat.game_mode).collect();
+        let turn_limit = self.rules.turn_limit_for(&game_modes);
+        let mut game =
+            GameState::new_with_modes(game_modes, stock, StdRng::seed_from_u64(self.seed));
+        game.players = players;
+        game.rules = Rules {
+            turn_limit,
+            ..self.rules.clone()
+        };
+        game.dealer_index = (self.current_player + player_count - 1) % player_count;
+        game.turns = self.turns;
+        game.verbose = false;
+        for (player_index, player) in game.players.iter().enumerate() {
+            let pairs = player.pairs.cards().clone();
+            let mut cards = pairs.clone();
+            cards.extend(player.hand.cards().iter().cloned());
+            game.events.push(GameEvent::Dealt {
+                player_index,
+                cards,
+            });
+            if !pairs.is_empty() {
+                game.events.push(GameEvent::LaidDown {
+                    player_index,
+                    cards: pairs,
+                });
+            }
+        }
+        check_invariants(&game).map_err(ScenarioError::Violation)?;
+        Ok(game)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::deck::parse_card;
+    use crate::game::{run_game, EndGameCondition};
+    use cardpack::Pile;
+
+    /// Scenario shipped as an example of the file format
+    const LAST_PAIRS: &str = include_str!("../scenarios/last-pairs.toml");
+
+    #[test]
+    fn test_build_places_every_card() {
+        let game = Scenario::new(Rules::default())
+            .player(GameMode::Random, "5C 9S")
+            .player(GameMode::Random, "5H KD")
+            .current_player(1)
+            .build()
+            .unwrap();
+        assert_eq!(game.players[0].hand, parse_pile("5C 9S").unwrap());
+        assert_eq!(game.deck.len(), 48);
+        assert!(!game.deck.contains(&parse_card("5H").unwrap()));
+        assert_eq!(game.first_player_index(), 1);
+        assert_eq!(
+            game.transcript(),
+            "player 0 is dealt 5C 9S\nplayer 1 is dealt 5H KD\n"
+        );
+    }
+
+    #[test]
+    fn test_build_keeps_stock_order() {
+        let mut scenario = Scenario::new(Rules::default())
+            .player(GameMode::Random, "")
+            .player(GameMode::Random, "");
+        scenario.stock = Some(Pile::french_deck().to_string());
+        let mut game = scenario.build().unwrap();
+        assert_eq!(game.deck, Pile::french_deck());
+        assert_eq!(game.deck.draw_first(), parse_card("AS"));
+    }
+
+    #[test]
+    fn test_build_rejects_bad_scenarios() {
+        let two_players = Scenario::new(Rules::default())
+            .player(GameMode::Random, "5C")
+            .player(GameMode::Random, "5C");
+        assert!(matches!(
+            two_players.build(),
+            Err(ScenarioError::Violation(RuleViolation::DuplicateCard(_)))
+        ));
+
+        let short_stock = Scenario::new(Rules::default())
+            .player(GameMode::Random, "5C")
+            .player(GameMode::Random, "5H")
+            .stock("5D 5S");
+        assert!(matches!(
+            short_stock.build(),
+            Err(ScenarioError::Violation(RuleViolation::MissingCard(_)))
+        ));
+
+        let lone = Scenario::new(Rules::default()).player(GameMode::Random, "5C");
+        assert!(matches!(lone.build(), Err(ScenarioError::Invalid(_))));
+
+        let unknown = Scenario::new(Rules::default())
+            .player(GameMode::Random, "5X")
+            .player(GameMode::Random, "");
+        assert!(matches!(unknown.build(), Err(ScenarioError::Invalid(_))));
+    }
+
+    #[test]
+    fn test_laid_down_cards_score() {
+        let mut scenario = Scenario::new(Rules::default())
+            .player(GameMode::Random, "")
+            .player(GameMode::Random, "");
+        scenario.players[1].pairs = "AS AH KS KH".to_string();
+        let game = scenario.build().unwrap();
+        assert_eq!(game.players[1].score, 2);
+        assert_eq!(game.players[0].score, 0);
+        assert_eq!(game.events[2].to_string(), "player 1 lays down AS AH KS KH");
+    }
+
+    #[test]
+    fn test_scenario_file_plays_out() {
+        let scenario = Scenario::from_toml(LAST_PAIRS).unwrap();
+        assert_eq!(scenario.players.len(), 3);
+        assert_eq!(scenario.players[2].game_mode, GameMode::Sequential);
+
+        let mut game = scenario.build().unwrap();
+        game.audit = true;
+        let standings = run_game(&mut game);
+        assert_ne!(standings.outcome, EndGameCondition::Continue);
+        assert!(Scenario::from_toml("preset = \"books\"\nplayers = []\n[rules]\n").is_err());
+    }
+}
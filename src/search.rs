@@ -0,0 +1,268 @@
+use crate::deck::DeckConfig;
+use crate::game::{run_game_from, EndGameCondition, GameMode, GameState, PlayerTurnResult};
+use crate::view::PlayerView;
+use rand::Rng;
+
+/// Number of hidden hands sampled for every decision
+pub const DEFAULT_DETERMINIZATIONS: usize = 8;
+
+/// Turns a rollout is played for before the scores are counted
+pub const DEFAULT_ROLLOUT_TURNS: u32 = 200;
+
+/// How hard the search strategy looks before it asks
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct SearchConfig {
+    /// Number of hidden hands sampled from the player's view
+    pub determinizations: usize,
+    /// Turns played in each rollout before the game is scored
+    pub rollout_turns: u32,
+}
+
+impl Default for SearchConfig {
+    fn default() -> Self {
+        SearchConfig {
+            determinizations: DEFAULT_DETERMINIZATIONS,
+            rollout_turns: DEFAULT_ROLLOUT_TURNS,
+        }
+    }
+}
+
+/// An ask a player can make: the card in their hand whose rank they ask for
+/// and the player they ask
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct Ask {
+    pub card_index: usize,
+    pub other_player_index: usize,
+}
+
+/// Get the asks worth considering, one for each rank in the hand and player to ask
+///
+/// Wild cards are only asked for when the hand holds nothing else and, when the
+/// game goes on after a player runs out of cards, only players holding cards are asked.
+pub fn legal_asks(view: &PlayerView) -> Vec<Ask> {
+    let cards = view.hand.cards();
+    let is_wild = |index: usize| {
+        view.rules
+            .wild
+            .as_ref()
+            .is_some_and(|wild| view.rules.families.is_none() && wild.cards.is_wild(&cards[index]))
+    };
+    let mut card_indices: Vec<usize> = (0..cards.len())
+        .filter(|&index| !is_wild(index))
+        .filter(|&index| (0..index).all(|earlier| cards[earlier].rank != cards[index].rank))
+        .collect();
+    if card_indices.is_empty() && !cards.is_empty() {
+        card_indices.push(0);
+    }
+
+    let player_count = view.player_count();
+    let others: Vec<usize> = (1..player_count)
+        .map(|offset| (view.player_index + offset) % player_count)
+        .collect();
+    let holding: Vec<usize> = others
+        .iter()
+        .copied()
+        .filter(|&index| view.hand_sizes[index] > 0)
+        .collect();
+    let asked = if holding.is_empty() { others } else { holding };
+
+    card_indices
+        .iter()
+        .flat_map(|&card_index| {
+            asked.iter().map(move |&other_player_index| Ask {
+                card_index,
+                other_player_index,
+            })
+        })
+        .collect()
+}
+
+/// Pick the ask that does best when the game is played out from sampled hidden hands
+///
+/// Every legal ask is tried on the same sampled games, the rest of each game is
+/// played out with every player asking at random, and the ask with the highest
+/// average result is chosen.
+///
+/// # Example
+///
+/// ```
+/// use rust_go_fish::search::{choose_ask, legal_asks, SearchConfig};
+/// use rust_go_fish::view::PlayerView;
+/// use rust_go_fish::{GameMode, GameState};
+/// use rand::rngs::StdRng;
+/// use rand::SeedableRng;
+///
+/// let mut game = GameState::new_seeded(vec![GameMode::Search, GameMode::Random], 3);
+/// game.verbose = false;
+/// game.deal();
+/// game.players_match_cards();
+///
+/// let view = PlayerView::from_game(&game, 0);
+/// let config = SearchConfig { determinizations: 2, rollout_turns: 20 };
+/// let ask = choose_ask(&view, &config, &mut StdRng::seed_from_u64(1));
+/// assert!(legal_asks(&view).contains(&ask));
+/// ```
+pub fn choose_ask<R: Rng + ?Sized>(view: &PlayerView, config: &SearchConfig, rng: &mut R) -> Ask {
+    let asks = legal_asks(view);
+    if asks.len() < 2 {
+        return asks.first().copied().unwrap_or(Ask {
+            card_index: 0,
+            other_player_index: (view.player_index + 1) % view.player_count(),
+        });
+    }
+
+    let scores = score_asks(view, config, rng);
+    let best =
+        scores.iter().enumerate().fold(
+            0,
+            |best, (index, (_, score))| {
+                if *score > scores[best].1 {
+                    index
+                } else {
+                    best
+                }
+            },
+        );
+    scores[best].0
+}
+
+/// Score every legal ask by its average result when the game is played out
+/// from sampled hidden hands, in the order of `legal_asks`
+///
+/// A result is one for a win and the player's share of a tie, plus a small
+/// amount for every point the player finishes ahead of the best other player.
+pub fn score_asks<R: Rng + ?Sized>(
+    view: &PlayerView,
+    config: &SearchConfig,
+    rng: &mut R,
+) -> Vec<(Ask, f64)> {
+    let asks = legal_asks(view);
+    let mut totals = vec![0.0; asks.len()];
+    for _ in 0..config.determinizations {
+        let sample = view.determinize(rng);
+        for (total, ask) in totals.iter_mut().zip(&asks) {
+            *total += rollout(
+                sample.clone(),
+                view.player_index,
+                *ask,
+                config.rollout_turns,
+            );
+        }
+    }
+    let samples = config.determinizations.max(1) as f64;
+    asks.into_iter()
+        .zip(totals)
+        .map(|(ask, total)| (ask, total / samples))
+        .collect()
+}
+
+/// Make an ask in a sampled game, play the game out at random and score it for the player
+///
+/// A win scores one and a shared win its share, plus a small amount for every
+/// point the player is ahead of the best other player.
+fn rollout(mut game: GameState, player_index: usize, ask: Ask, rollout_turns: u32) -> f64 {
+    game.verbose = false;
+    for player in game.players.iter_mut() {
+        player.game_mode = GameMode::Random;
+    }
+    let turn_limit = game.turns + rollout_turns;
+    game.rules.turn_limit = Some(
+        game.rules
+            .turn_limit
+            .map_or(turn_limit, |limit| limit.min(turn_limit)),
+    );
+
+    game.turns += 1;
+    let turn_result = game.perform_turn(player_index, ask.other_player_index, ask.card_index);
+    let mut outcome = game.check_win_condition();
+    if outcome == EndGameCondition::Continue {
+        let next_player_index = match turn_result {
+            PlayerTurnResult::PlayAgain => player_index,
+            PlayerTurnResult::NextPlayerTurn => game.next_player_index(player_index),
+        };
+        outcome = run_game_from(&mut game, next_player_index).outcome;
+    }
+
+    let result = match outcome {
+        EndGameCondition::Winner(winner) if winner == player_index => 1.0,
+        EndGameCondition::Tie(winners) if winners.contains(&player_index) => {
+            1.0 / winners.len() as f64
+        }
+        _ => 0.0,
+    };
+    let score = game.players[player_index].score as f64;
+    let best_other = game
+        .players
+        .iter()
+        .enumerate()
+        .filter(|&(index, _)| index != player_index)
+        .map(|(_, player)| player.score)
+        .max()
+        .unwrap_or(0) as f64;
+    result + (score - best_other) / max_score(&game.rules.deck)
+}
+
+/// Get the most points a game could score, used to keep the score margin below a win
+fn max_score(deck: &DeckConfig) -> f64 {
+    (deck.build().len() as f64).max(1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hand::Hand;
+    use crate::rules::Rules;
+    use cardpack::Standard52;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn setup() -> GameState {
+        let modes = vec![GameMode::Search, GameMode::Random, GameMode::Random];
+        let mut game = GameState::new_with_rules(modes, Rules::default(), StdRng::seed_from_u64(2));
+        game.verbose = false;
+        game.deal();
+        game.players_match_cards();
+        game
+    }
+
+    #[test]
+    fn test_legal_asks_one_per_rank_and_player() {
+        let mut game = setup();
+        game.players[0].hand = Standard52::pile_from_index("5C 9S 7D").unwrap().into();
+        let asks = legal_asks(&PlayerView::from_game(&game, 0));
+        assert_eq!(asks.len(), 3 * 2);
+
+        game.players[2].hand = Hand::new();
+        game.rules.empty_hand = crate::rules::EmptyHand::SitOut;
+        let asks = legal_asks(&PlayerView::from_game(&game, 0));
+        assert!(asks.iter().all(|ask| ask.other_player_index == 1));
+    }
+
+    #[test]
+    fn test_choose_ask_takes_the_sure_pair() {
+        let mut game = setup();
+        game.players[0].hand = Standard52::pile_from_index("5C 9S").unwrap().into();
+        game.players[1].hand = Standard52::pile_from_index("9D KH").unwrap().into();
+        game.players[2].hand = Standard52::pile_from_index("2S 3S").unwrap().into();
+        game.deck = Standard52::pile_from_index("4H 6H 7H 8H").unwrap();
+        // player 1 asks player 2 for nines and goes fishing, so player 1 holds a nine
+        game.perform_turn(1, 2, 0);
+
+        let view = PlayerView::from_game(&game, 0);
+        let config = SearchConfig {
+            determinizations: 4,
+            rollout_turns: 1,
+        };
+        let ask = choose_ask(&view, &config, &mut StdRng::seed_from_u64(3));
+        assert_eq!(view.hand.cards()[ask.card_index].index, "9S");
+        assert_eq!(ask.other_player_index, 1);
+    }
+
+    #[test]
+    fn test_search_game_mode_plays_a_game() {
+        let mut game = setup();
+        game.rules.turn_limit = Some(30);
+        let standings = crate::game::run_game(&mut game);
+        assert_ne!(standings.outcome, EndGameCondition::Continue);
+    }
+}
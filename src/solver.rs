@@ -0,0 +1,412 @@
+use crate::game::{EndGameCondition, GameState, PlayerTurnResult};
+use crate::search::legal_asks;
+use crate::view::PlayerView;
+use cardpack::{Card, Pile};
+use std::collections::HashMap;
+use std::fmt;
+
+/// Number of positions the solver looks at before giving up on an endgame
+pub const DEFAULT_MAX_POSITIONS: usize = 100_000;
+
+/// Why an endgame could not be solved
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum SolveError {
+    /// The game has already ended
+    GameOver,
+    /// The endgame has more positions than the solver was allowed to look at
+    TooLarge(usize),
+}
+
+impl fmt::Display for SolveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SolveError::GameOver => write!(f, "the game is already over"),
+            SolveError::TooLarge(limit) => {
+                write!(f, "the endgame has more than {} positions", limit)
+            }
+        }
+    }
+}
+
+impl std::error::Error for SolveError {}
+
+/// The value of an ask with perfect play afterwards
+#[derive(Debug, PartialEq, Clone)]
+pub struct AskValue {
+    /// The card asked for, a rank in Go Fish and an exact member in Happy Families
+    pub card: Card,
+    pub other_player_index: usize,
+    /// Expected result of every player after the ask, by player index
+    pub values: Vec<f64>,
+}
+
+/// The solved value of a position for the player to move
+#[derive(Debug, PartialEq, Clone)]
+pub struct Solution {
+    pub player_index: usize,
+    /// Expected result of every player with perfect play, by player index
+    pub values: Vec<f64>,
+    /// Every ask the player can make, empty when their hand is empty
+    pub asks: Vec<AskValue>,
+}
+
+impl Solution {
+    /// Get the ask with the highest value for the player, the first one on ties
+    pub fn best_ask(&self) -> Option<&AskValue> {
+        self.asks
+            .iter()
+            .fold(None, |best: Option<&AskValue>, ask| match best {
+                Some(best) if best.values[self.player_index] >= ask.values[self.player_index] => {
+                    Some(best)
+                }
+                _ => Some(ask),
+            })
+    }
+
+    /// Get how much worse an ask is than the best one for the player, zero for a
+    /// best ask and `None` for an ask the player can not make
+    pub fn loss(&self, card: &Card, other_player_index: usize) -> Option<f64> {
+        let best = self.best_ask()?.values[self.player_index];
+        self.asks
+            .iter()
+            .find(|ask| ask.card == *card && ask.other_player_index == other_player_index)
+            .map(|ask| best - ask.values[self.player_index])
+    }
+}
+
+/// Solve a small endgame with every hand and the stock known
+///
+/// Every player makes the ask that is best for themselves, and every draw from
+/// the stock is averaged over the cards that could be on top. A player's result
+/// is one for a win, their share for a tie and zero for a loss. The turn limit
+/// is ignored; a position repeated along a line of play ends the game there,
+/// scored as if the turn limit was reached.
+///
+/// # Example
+///
+/// ```
+/// use rust_go_fish::solver::{solve, DEFAULT_MAX_POSITIONS};
+/// use rust_go_fish::{GameMode, GameState};
+/// use cardpack::{Pile, Standard52};
+///
+/// let mut game = GameState::new_seeded(vec![GameMode::Random; 2], 1);
+/// game.players[0].hand = Standard52::pile_from_index("5C 9S").unwrap().into();
+/// game.players[1].hand = Standard52::pile_from_index("5H 9D").unwrap().into();
+/// game.deck = Pile::default();
+///
+/// let solution = solve(&game, 0, DEFAULT_MAX_POSITIONS).unwrap();
+/// assert_eq!(solution.values, vec![1.0, 0.0]);
+/// ```
+pub fn solve(
+    game: &GameState,
+    player_index: usize,
+    max_positions: usize,
+) -> Result<Solution, SolveError> {
+    let mut game = game.clone();
+    game.verbose = false;
+    game.rules.turn_limit = None;
+    if game.check_win_condition() != EndGameCondition::Continue {
+        return Err(SolveError::GameOver);
+    }
+
+    let mut solver = Solver {
+        max_positions,
+        positions: 0,
+        solved: HashMap::new(),
+    };
+    let mut path = vec![position_key(&game, player_index)];
+    if game.players[player_index].hand.is_empty() {
+        let (values, _) = solver.chance_value(&game, player_index, &mut path, |game| {
+            game.play_turn(player_index)
+        })?;
+        return Ok(Solution {
+            player_index,
+            values,
+            asks: Vec::new(),
+        });
+    }
+
+    let mut asks = Vec::new();
+    for (card, other_player_index) in asks_for(&game, player_index) {
+        let (values, _) = solver.chance_value(&game, player_index, &mut path, |game| {
+            game.perform_ask(player_index, other_player_index, card.clone())
+        })?;
+        asks.push(AskValue {
+            card,
+            other_player_index,
+            values,
+        });
+    }
+    let mut solution = Solution {
+        player_index,
+        values: Vec::new(),
+        asks,
+    };
+    solution.values = solution
+        .best_ask()
+        .map(|ask| ask.values.clone())
+        .unwrap_or_default();
+    Ok(solution)
+}
+
+/// Expectimax search over the positions of an endgame
+struct Solver {
+    max_positions: usize,
+    positions: usize,
+    /// Values of positions solved without cutting a repeated position short
+    solved: HashMap<String, Vec<f64>>,
+}
+
+impl Solver {
+    /// Get the value of the position with the given player to move, and whether
+    /// it holds on every line of play that reaches it
+    fn value(
+        &mut self,
+        game: GameState,
+        player_index: usize,
+        path: &mut Vec<String>,
+    ) -> Result<(Vec<f64>, bool), SolveError> {
+        let outcome = game.check_win_condition();
+        if outcome != EndGameCondition::Continue {
+            return Ok((outcome_values(&outcome, game.player_count), true));
+        }
+        let key = position_key(&game, player_index);
+        if let Some(values) = self.solved.get(&key) {
+            return Ok((values.clone(), true));
+        }
+        if path.contains(&key) {
+            return Ok((
+                outcome_values(&game.determine_winner(), game.player_count),
+                false,
+            ));
+        }
+        self.positions += 1;
+        if self.positions > self.max_positions {
+            return Err(SolveError::TooLarge(self.max_positions));
+        }
+
+        path.push(key);
+        let result = if game.players[player_index].hand.is_empty() {
+            self.chance_value(&game, player_index, path, |game| {
+                game.play_turn(player_index)
+            })
+        } else {
+            self.best_value(&game, player_index, path)
+        };
+        let key = path.pop().unwrap_or_default();
+        if let Ok((values, true)) = &result {
+            self.solved.insert(key, values.clone());
+        }
+        result
+    }
+
+    /// Get the value of the ask that is best for the player to move
+    fn best_value(
+        &mut self,
+        game: &GameState,
+        player_index: usize,
+        path: &mut Vec<String>,
+    ) -> Result<(Vec<f64>, bool), SolveError> {
+        let mut best: Option<Vec<f64>> = None;
+        let mut exact = true;
+        for (card, other_player_index) in asks_for(game, player_index) {
+            let (values, ask_exact) = self.chance_value(game, player_index, path, |game| {
+                game.perform_ask(player_index, other_player_index, card.clone())
+            })?;
+            exact &= ask_exact;
+            // is_none_or would need Rust 1.82
+            #[allow(clippy::unnecessary_map_or)]
+            if best
+                .as_ref()
+                .map_or(true, |best| values[player_index] > best[player_index])
+            {
+                best = Some(values);
+            }
+        }
+        Ok((best.unwrap_or_else(|| vec![0.0; game.player_count]), exact))
+    }
+
+    /// Get the value of a turn, averaged over the card on top of the stock if the
+    /// turn draws from it
+    fn chance_value<F>(
+        &mut self,
+        game: &GameState,
+        player_index: usize,
+        path: &mut Vec<String>,
+        turn: F,
+    ) -> Result<(Vec<f64>, bool), SolveError>
+    where
+        F: Fn(&mut GameState) -> PlayerTurnResult,
+    {
+        let mut played = game.clone();
+        let turn_result = turn(&mut played);
+        if played.deck.len() == game.deck.len() {
+            return self.value_after(played, player_index, turn_result, path);
+        }
+
+        let mut tops: Vec<(Card, usize)> = Vec::new();
+        for card in game.deck.cards() {
+            match tops.iter_mut().find(|(top, _)| top == card) {
+                Some((_, count)) => *count += 1,
+                None => tops.push((card.clone(), 1)),
+            }
+        }
+        let mut values = vec![0.0; game.player_count];
+        let mut exact = true;
+        for (card, count) in tops {
+            let mut drawn = game.clone();
+            drawn.deck.remove_card(&card);
+            drawn.deck.prepend(&Pile::from_vector(vec![card]));
+            let turn_result = turn(&mut drawn);
+            let (top_values, top_exact) =
+                self.value_after(drawn, player_index, turn_result, path)?;
+            let weight = count as f64 / game.deck.len() as f64;
+            for (value, top_value) in values.iter_mut().zip(top_values) {
+                *value += weight * top_value;
+            }
+            exact &= top_exact;
+        }
+        Ok((values, exact))
+    }
+
+    /// Get the value of the position after a player's turn
+    fn value_after(
+        &mut self,
+        game: GameState,
+        player_index: usize,
+        turn_result: PlayerTurnResult,
+        path: &mut Vec<String>,
+    ) -> Result<(Vec<f64>, bool), SolveError> {
+        let next_player_index = match turn_result {
+            PlayerTurnResult::PlayAgain => player_index,
+            PlayerTurnResult::NextPlayerTurn => game.next_player_index(player_index),
+        };
+        self.value(game, next_player_index, path)
+    }
+}
+
+/// Get the asks a player can make, one for each rank and player to ask in Go
+/// Fish and one for each missing member and player to ask in Happy Families
+fn asks_for(game: &GameState, player_index: usize) -> Vec<(Card, usize)> {
+    let view = PlayerView::from_game(game, player_index);
+    let mut asks: Vec<(Card, usize)> = Vec::new();
+    for ask in legal_asks(&view) {
+        let card = view.hand.cards()[ask.card_index].clone();
+        let cards = match &game.rules.families {
+            Some(families) => families
+                .members(card.rank)
+                .into_iter()
+                .filter(|member| !view.hand.contains(member))
+                .collect(),
+            None => vec![card],
+        };
+        for card in cards {
+            let ask = (card, ask.other_player_index);
+            if !asks.contains(&ask) {
+                asks.push(ask);
+            }
+        }
+    }
+    asks
+}
+
+/// Get every player's result for the end of a game
+fn outcome_values(outcome: &EndGameCondition, player_count: usize) -> Vec<f64> {
+    let mut values = vec![0.0; player_count];
+    match outcome {
+        EndGameCondition::Winner(winner) => values[*winner] = 1.0,
+        EndGameCondition::Tie(winners) => {
+            for &winner in winners {
+                values[winner] = 1.0 / winners.len() as f64;
+            }
+        }
+        EndGameCondition::Continue => {}
+    }
+    values
+}
+
+/// Get a key for everything about a position that can change how it plays out,
+/// with the cards of every pile in a fixed order
+fn position_key(game: &GameState, player_index: usize) -> String {
+    let sorted = |cards: &[Card]| {
+        let mut indices: Vec<&str> = cards.iter().map(|card| card.index.as_str()).collect();
+        indices.sort_unstable();
+        indices.join(" ")
+    };
+    let players: Vec<String> = game
+        .players
+        .iter()
+        .map(|player| {
+            format!(
+                "{}|{}|{}",
+                sorted(player.hand.cards()),
+                sorted(player.pairs.cards()),
+                player.score
+            )
+        })
+        .collect();
+    format!(
+        "{}/{}/{}/{:?}",
+        player_index,
+        players.join("/"),
+        sorted(game.deck.cards()),
+        game.went_out
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::deck::parse_card;
+    use crate::game::GameMode;
+    use cardpack::Standard52;
+
+    fn setup(hands: &[&'static str], deck: &'static str) -> GameState {
+        let mut game = GameState::new_seeded(vec![GameMode::Sequential; hands.len()], 0);
+        game.verbose = false;
+        for (player, hand) in game.players.iter_mut().zip(hands) {
+            player.hand = Standard52::pile_from_index(hand).unwrap().into();
+        }
+        game.deck = Standard52::pile_from_index(deck).unwrap_or_default();
+        game
+    }
+
+    #[test]
+    fn test_solve_values_every_ask() {
+        // asking player 1 for fives makes a pair and empties the hand, asking
+        // player 2 lets player 1 take both pairs
+        let game = setup(&["5C", "5H 7S", "7D"], "");
+        let solution = solve(&game, 0, DEFAULT_MAX_POSITIONS).unwrap();
+
+        assert_eq!(solution.asks.len(), 2);
+        let best = solution.best_ask().unwrap();
+        assert_eq!(best.other_player_index, 1);
+        assert_eq!(solution.values, vec![1.0, 0.0, 0.0]);
+
+        let five = parse_card("5C").unwrap();
+        assert_eq!(solution.loss(&five, 1), Some(0.0));
+        assert_eq!(solution.loss(&five, 2), Some(1.0));
+    }
+
+    #[test]
+    fn test_solve_averages_the_draw() {
+        // fishing the five wins for player 0, fishing the nine lets player 1 pair up
+        let game = setup(&["5C", "9S"], "5H 9D");
+        let solution = solve(&game, 0, DEFAULT_MAX_POSITIONS).unwrap();
+        assert_eq!(solution.values, vec![0.5, 0.5]);
+    }
+
+    #[test]
+    fn test_solve_errors() {
+        let game = setup(&["", "5H"], "");
+        assert_eq!(
+            solve(&game, 1, DEFAULT_MAX_POSITIONS),
+            Err(SolveError::GameOver)
+        );
+
+        let mut game = GameState::new_seeded(vec![GameMode::Random; 3], 2);
+        game.deal();
+        game.players_match_cards();
+        assert_eq!(solve(&game, 0, 50), Err(SolveError::TooLarge(50)));
+    }
+}
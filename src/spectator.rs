@@ -0,0 +1,240 @@
+use crate::game::{card_indices, GameEvent, GameState};
+use std::fmt;
+use std::str::FromStr;
+
+/// Whose eyes a game is watched through
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum Perspective {
+    /// A player at the table, who sees their own hand and what is public
+    Seat(usize),
+    /// Somebody watching the table, who sees only what is public
+    Spectator,
+    /// Every hand and the order of the stock, for debugging and commentary
+    Omniscient,
+}
+
+impl fmt::Display for Perspective {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Perspective::Seat(player_index) => write!(f, "seat:{}", player_index),
+            Perspective::Spectator => write!(f, "spectator"),
+            Perspective::Omniscient => write!(f, "god"),
+        }
+    }
+}
+
+impl FromStr for Perspective {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "spectator" => Ok(Perspective::Spectator),
+            "god" => Ok(Perspective::Omniscient),
+            _ => s
+                .strip_prefix("seat:")
+                .and_then(|index| index.parse().ok())
+                .map(Perspective::Seat)
+                .ok_or(format!("unknown view: {}", s)),
+        }
+    }
+}
+
+impl Perspective {
+    /// Check if a player's hand and the cards they draw are seen
+    pub fn sees_hand(&self, player_index: usize) -> bool {
+        match self {
+            Perspective::Seat(seat) => *seat == player_index,
+            Perspective::Spectator => false,
+            Perspective::Omniscient => true,
+        }
+    }
+
+    /// Describe an event as seen from this perspective, without the cards it does not see
+    ///
+    /// Cards asked for, handed over and laid down are shown to the whole table,
+    /// the cards dealt and drawn only to the player receiving them.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rust_go_fish::deck::parse_card;
+    /// use rust_go_fish::spectator::Perspective;
+    /// use rust_go_fish::GameEvent;
+    ///
+    /// let event = GameEvent::Drew { player_index: 1, card: parse_card("7S").unwrap() };
+    /// assert_eq!(Perspective::Spectator.describe(&event), "player 1 draws a card");
+    /// assert_eq!(Perspective::Seat(1).describe(&event), "player 1 draws 7S");
+    /// assert_eq!(Perspective::Omniscient.describe(&event), "player 1 draws 7S");
+    /// ```
+    pub fn describe(&self, event: &GameEvent) -> String {
+        match event {
+            GameEvent::Dealt {
+                player_index,
+                cards,
+            } if !self.sees_hand(*player_index) => {
+                format!("player {} is dealt {} cards", player_index, cards.len())
+            }
+            GameEvent::Drew { player_index, .. } if !self.sees_hand(*player_index) => {
+                format!("player {} draws a card", player_index)
+            }
+            _ => event.to_string(),
+        }
+    }
+
+    /// Get the events of a game so far as seen from this perspective, one per line
+    pub fn transcript(&self, game: &GameState) -> String {
+        game.events
+            .iter()
+            .map(|event| format!("{}\n", self.describe(event)))
+            .collect()
+    }
+
+    /// Draw the table as seen from this perspective: every hand, or its size when
+    /// it is not seen, the cards laid down, the scores and the stock
+    ///
+    /// Only the omniscient view shows the stock, from the top card down.
+    pub fn table(&self, game: &GameState) -> String {
+        let mut table = String::new();
+        for (player_index, player) in game.players.iter().enumerate() {
+            let hand = if self.sees_hand(player_index) {
+                card_indices(player.hand.cards())
+            } else {
+                format!("{} cards", player.hand.len())
+            };
+            table.push_str(&format!(
+                "player {}  score {}  hand: {}\n",
+                player_index, player.score, hand
+            ));
+            if !player.pairs.is_empty() {
+                table.push_str(&format!(
+                    "          laid down: {}\n",
+                    card_indices(player.pairs.cards())
+                ));
+            }
+        }
+        if *self == Perspective::Omniscient {
+            table.push_str(&format!("stock: {}\n", card_indices(game.deck.cards())));
+        } else {
+            table.push_str(&format!("stock: {} cards\n", game.deck.len()));
+        }
+        table
+    }
+}
+
+/// A stream of the events of a game as seen from a perspective, handing out
+/// every event once as the game goes on
+///
+/// # Example
+///
+/// ```
+/// use rust_go_fish::spectator::{Perspective, SpectatorStream};
+/// use rust_go_fish::{GameMode, GameState};
+///
+/// let mut game = GameState::new_seeded(vec![GameMode::Random; 2], 3);
+/// game.verbose = false;
+/// let mut stream = SpectatorStream::new(Perspective::Spectator);
+/// game.deal();
+/// assert_eq!(stream.next_lines(&game), vec!["player 0 is dealt 7 cards", "player 1 is dealt 7 cards"]);
+/// assert!(stream.next_lines(&game).is_empty());
+/// ```
+#[derive(Debug, Clone)]
+pub struct SpectatorStream {
+    pub perspective: Perspective,
+    /// Number of events already handed out
+    seen: usize,
+}
+
+impl SpectatorStream {
+    pub fn new(perspective: Perspective) -> Self {
+        SpectatorStream {
+            perspective,
+            seen: 0,
+        }
+    }
+
+    /// Get the events recorded since the last call, as seen from the perspective
+    ///
+    /// If events were taken back, as by an undo, the stream goes on from the
+    /// last event still recorded.
+    pub fn next_lines(&mut self, game: &GameState) -> Vec<String> {
+        self.seen = self.seen.min(game.events.len());
+        let lines = game.events[self.seen..]
+            .iter()
+            .map(|event| self.perspective.describe(event))
+            .collect();
+        self.seen = game.events.len();
+        lines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{run_game, GameMode};
+    use crate::scenario::Scenario;
+    use crate::Rules;
+
+    fn setup() -> GameState {
+        let mut scenario = Scenario::new(Rules::default())
+            .player(GameMode::Sequential, "5C 9S")
+            .player(GameMode::Sequential, "5H KD");
+        scenario.players[1].pairs = "AS AH".to_string();
+        scenario.build().unwrap()
+    }
+
+    #[test]
+    fn test_perspective_from_str() {
+        assert_eq!("spectator".parse(), Ok(Perspective::Spectator));
+        assert_eq!("god".parse(), Ok(Perspective::Omniscient));
+        assert_eq!("seat:2".parse(), Ok(Perspective::Seat(2)));
+        assert!("seat:x".parse::<Perspective>().is_err());
+        assert_eq!(Perspective::Seat(2).to_string(), "seat:2");
+    }
+
+    #[test]
+    fn test_table_hides_unseen_hands() {
+        let game = setup();
+        assert_eq!(
+            Perspective::Spectator.table(&game),
+            "player 0  score 0  hand: 2 cards\n\
+             player 1  score 1  hand: 2 cards\n          laid down: AS AH\n\
+             stock: 46 cards\n"
+        );
+        let seat = Perspective::Seat(0).table(&game);
+        assert!(seat.contains("hand: 5C 9S"));
+        assert!(seat.contains("player 1  score 1  hand: 2 cards"));
+
+        let god = Perspective::Omniscient.table(&game);
+        assert!(god.contains("player 1  score 1  hand: 5H KD"));
+        assert!(god.ends_with(&format!("stock: {}\n", card_indices(game.deck.cards()))));
+    }
+
+    #[test]
+    fn test_spectator_transcript_shows_only_public_cards() {
+        let mut game = setup();
+        run_game(&mut game);
+        let spectator = Perspective::Spectator.transcript(&game);
+        let god = Perspective::Omniscient.transcript(&game);
+        assert_eq!(god, game.transcript());
+        assert_eq!(spectator.lines().count(), god.lines().count());
+        assert!(spectator.starts_with("player 0 is dealt 2 cards\nplayer 1 is dealt 4 cards\n"));
+        assert!(spectator.contains("draws a card"));
+        assert!(!god.contains("draws a card"));
+    }
+
+    #[test]
+    fn test_stream_hands_out_every_event_once() {
+        let mut game = setup();
+        let mut stream = SpectatorStream::new(Perspective::Seat(0));
+        let mut lines = stream.next_lines(&game);
+        game.play_turn(0);
+        lines.extend(stream.next_lines(&game));
+        assert_eq!(
+            lines.join("\n") + "\n",
+            Perspective::Seat(0).transcript(&game)
+        );
+
+        game.events.truncate(1);
+        assert!(stream.next_lines(&game).is_empty());
+    }
+}
@@ -0,0 +1,229 @@
+use crate::game::{EndGameCondition, GameState};
+use crate::rules::TieBreaker;
+use serde::Serialize;
+use std::fmt;
+
+/// A single player's line in the final standings
+#[derive(Debug, PartialEq, Eq, Clone, Serialize)]
+pub struct PlayerStanding {
+    /// Finishing position, players with equal scores share a rank
+    pub rank: usize,
+    pub player_index: usize,
+    pub score: u32,
+    /// Number of pairs, or families in Happy Families, laid down
+    pub pairs_made: usize,
+    /// The laid down cards as an index string
+    pub pairs: String,
+    pub cards_in_hand: usize,
+    pub successful_asks: u32,
+    pub go_fishes: u32,
+}
+
+/// The result of a finished game with a line for every player,
+/// ordered from first to last place
+#[derive(Debug, PartialEq, Eq, Clone, Serialize)]
+pub struct Standings {
+    pub outcome: EndGameCondition,
+    /// The tie breaker that decided the outcome, if the top scores were tied
+    pub tie_break: Option<TieBreaker>,
+    pub players: Vec<PlayerStanding>,
+}
+
+impl Standings {
+    /// Build the standings from the final state of a game
+    ///
+    /// Players are ordered by score, highest first. Players with the same
+    /// score share a rank and the next rank is skipped (1, 1, 3). When a tie
+    /// breaker decided the winner, the winner comes first with rank 1 and the
+    /// players they were tied with share rank 2.
+    pub fn from_game(
+        outcome: EndGameCondition,
+        tie_break: Option<TieBreaker>,
+        game: &GameState,
+    ) -> Self {
+        let mut players: Vec<PlayerStanding> = game
+            .players
+            .iter()
+            .enumerate()
+            .map(|(index, player)| PlayerStanding {
+                rank: 0,
+                player_index: index,
+                score: player.score,
+                pairs_made: player.pairs.len() / game.rules.book_size,
+                pairs: player.pairs.to_index(),
+                cards_in_hand: player.hand.len(),
+                successful_asks: player.successful_asks,
+                go_fishes: player.go_fishes,
+            })
+            .collect();
+
+        let tie_broken_winner = match (&outcome, tie_break) {
+            (EndGameCondition::Winner(winner_index), Some(_)) => Some(*winner_index),
+            _ => None,
+        };
+        let is_tie_broken_winner =
+            |standing: &PlayerStanding| tie_broken_winner == Some(standing.player_index);
+
+        players.sort_by(|a, b| {
+            b.score
+                .cmp(&a.score)
+                .then(is_tie_broken_winner(b).cmp(&is_tie_broken_winner(a)))
+                .then(a.player_index.cmp(&b.player_index))
+        });
+
+        for position in 0..players.len() {
+            players[position].rank = if position > 0
+                && players[position].score == players[position - 1].score
+                && !is_tie_broken_winner(&players[position - 1])
+            {
+                players[position - 1].rank
+            } else {
+                position + 1
+            };
+        }
+
+        Standings {
+            outcome,
+            tie_break,
+            players,
+        }
+    }
+
+    /// Serialize the standings as pretty printed JSON
+    pub fn to_json(&self) -> String {
+        // Serializing plain structs, enums and strings can not fail
+        serde_json::to_string_pretty(self).unwrap()
+    }
+}
+
+impl fmt::Display for Standings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.outcome {
+            EndGameCondition::Winner(winner_index) => {
+                writeln!(f, "The winner is player at index {}", winner_index)?;
+            }
+            EndGameCondition::Tie(tie_indices) => {
+                writeln!(
+                    f,
+                    "There is a tie between players at indices {:?}",
+                    tie_indices
+                )?;
+            }
+            EndGameCondition::Continue => {
+                writeln!(f, "The game is still in progress")?;
+            }
+        }
+        if let Some(tie_break) = &self.tie_break {
+            writeln!(f, "Tie broken by {}", tie_break)?;
+        }
+
+        writeln!(
+            f,
+            "{:>4}  {:>6}  {:>5}  {:>5}  {:>4}  {:>4}  {:>7}",
+            "Rank", "Player", "Score", "Pairs", "Hand", "Asks", "GoFish"
+        )?;
+        for standing in &self.players {
+            writeln!(
+                f,
+                "{:>4}  {:>6}  {:>5}  {:>5}  {:>4}  {:>4}  {:>7}",
+                standing.rank,
+                standing.player_index,
+                standing.score,
+                standing.pairs_made,
+                standing.cards_in_hand,
+                standing.successful_asks,
+                standing.go_fishes
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::GameMode;
+    use cardpack::Standard52;
+
+    fn setup() -> GameState {
+        let standard52 = Standard52::default();
+        let mut game = GameState::new_with_deck(4, standard52, GameMode::Sequential);
+        game.verbose = false;
+        game.deal();
+        game.players_match_cards();
+        game
+    }
+
+    #[test]
+    fn test_standings_shared_rank() {
+        let mut game = setup();
+        game.players[0].score = 3;
+        game.players[1].score = 5;
+        game.players[2].score = 3;
+        game.players[3].score = 1;
+        let standings = Standings::from_game(EndGameCondition::Winner(1), None, &game);
+
+        let order: Vec<(usize, usize)> = standings
+            .players
+            .iter()
+            .map(|standing| (standing.rank, standing.player_index))
+            .collect();
+        assert_eq!(order, vec![(1, 1), (2, 0), (2, 2), (4, 3)]);
+    }
+
+    #[test]
+    fn test_standings_tie_broken_winner_comes_first() {
+        let mut game = setup();
+        game.players[0].score = 4;
+        game.players[1].score = 2;
+        game.players[2].score = 4;
+        game.players[3].score = 4;
+        let standings = Standings::from_game(
+            EndGameCondition::Winner(2),
+            Some(TieBreaker::FewestCardsInHand),
+            &game,
+        );
+
+        let order: Vec<(usize, usize)> = standings
+            .players
+            .iter()
+            .map(|standing| (standing.rank, standing.player_index))
+            .collect();
+        assert_eq!(order, vec![(1, 2), (2, 0), (2, 3), (4, 1)]);
+    }
+
+    #[test]
+    fn test_standings_to_json() {
+        let game = setup();
+        let standings = Standings::from_game(EndGameCondition::Tie(vec![0, 1]), None, &game);
+        let json: serde_json::Value = serde_json::from_str(&standings.to_json()).unwrap();
+
+        assert_eq!(json["outcome"]["Tie"], serde_json::json!([0, 1]));
+        assert_eq!(json["tie_break"], serde_json::Value::Null);
+        assert_eq!(json["players"].as_array().unwrap().len(), 4);
+        assert_eq!(json["players"][0]["cards_in_hand"], 7);
+    }
+
+    #[test]
+    fn test_standings_display_has_a_row_per_player() {
+        let game = setup();
+        let standings = Standings::from_game(EndGameCondition::Winner(0), None, &game);
+        let table = standings.to_string();
+
+        assert!(table.starts_with("The winner is player at index 0"));
+        assert_eq!(table.lines().count(), 2 + game.players.len());
+    }
+
+    #[test]
+    fn test_standings_display_tie_break() {
+        let game = setup();
+        let standings = Standings::from_game(
+            EndGameCondition::Winner(0),
+            Some(TieBreaker::FewestCardsInHand),
+            &game,
+        );
+        assert!(standings
+            .to_string()
+            .contains("Tie broken by fewest cards left in hand"));
+    }
+}
@@ -0,0 +1,503 @@
+use crate::game::{EndGameCondition, GameEvent, GameState};
+use crate::rules::EmptyHand;
+use crate::standings::Standings;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[derive(Debug)]
+pub enum StatsError {
+    Io(io::Error),
+    /// A line of the statistics file, counted from 1, is not a valid game record
+    Format(usize, serde_json::Error),
+}
+
+impl fmt::Display for StatsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StatsError::Io(error) => {
+                write!(f, "could not access the statistics file: {}", error)
+            }
+            StatsError::Format(line, error) => write!(
+                f,
+                "line {} of the statistics file is not valid: {}",
+                line, error
+            ),
+        }
+    }
+}
+
+impl std::error::Error for StatsError {}
+
+impl From<io::Error> for StatsError {
+    fn from(error: io::Error) -> Self {
+        StatsError::Io(error)
+    }
+}
+
+/// The rules a recorded game was played with, in short
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct GameConfig {
+    pub player_count: usize,
+    /// Number of cards in the deck before the deal
+    pub deck_size: usize,
+    pub hand_size: usize,
+    pub book_size: usize,
+    pub empty_hand: EmptyHand,
+    pub surrender_all: bool,
+    pub fish_your_wish: bool,
+    pub wild_cards: bool,
+    pub happy_families: bool,
+    pub turn_limit: Option<u32>,
+}
+
+impl GameConfig {
+    pub fn from_game(game: &GameState) -> Self {
+        let rules = &game.rules;
+        GameConfig {
+            player_count: game.player_count,
+            deck_size: rules.deck.build().len(),
+            hand_size: rules.hand_size_for(game.player_count),
+            book_size: rules.book_size,
+            empty_hand: rules.empty_hand,
+            surrender_all: rules.surrender_all,
+            fish_your_wish: rules.fish_your_wish,
+            wild_cards: rules.wild.is_some(),
+            happy_families: rules.families.is_some(),
+            turn_limit: rules.turn_limit,
+        }
+    }
+}
+
+/// How often each kind of action happened over a game
+#[derive(Debug, PartialEq, Eq, Clone, Default, Serialize, Deserialize)]
+pub struct TurnCounts {
+    pub turns: u32,
+    pub asks: u32,
+    pub cards_handed_over: u32,
+    pub go_fishes: u32,
+    pub draws: u32,
+    /// Pairs, books or families laid down
+    pub books_laid_down: u32,
+}
+
+impl TurnCounts {
+    pub fn from_game(game: &GameState) -> Self {
+        let mut counts = TurnCounts {
+            turns: game.turns,
+            ..TurnCounts::default()
+        };
+        for event in &game.events {
+            match event {
+                GameEvent::Asked { .. } => counts.asks += 1,
+                GameEvent::HandedOver { cards, .. } => {
+                    counts.cards_handed_over += cards.len() as u32
+                }
+                GameEvent::GoFish { .. } => counts.go_fishes += 1,
+                GameEvent::Drew { .. } => counts.draws += 1,
+                GameEvent::LaidDown { cards, .. } => {
+                    counts.books_laid_down += (cards.len() / game.rules.book_size) as u32
+                }
+                GameEvent::Dealt { .. } | GameEvent::SatOut { .. } => {}
+            }
+        }
+        counts
+    }
+}
+
+/// A player's seat in a recorded game and how they did
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct RecordedPlayer {
+    pub name: String,
+    /// The game mode the player picked their asks with
+    pub strategy: String,
+    pub score: u32,
+    pub cards_in_hand: usize,
+    pub successful_asks: u32,
+    pub go_fishes: u32,
+}
+
+/// How a game ended for one player
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Serialize, Deserialize)]
+pub enum PlayerResult {
+    Win,
+    Loss,
+    /// The player shared the highest score and no tie breaker separated them
+    Tie,
+}
+
+impl fmt::Display for PlayerResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let letter = match self {
+            PlayerResult::Win => "W",
+            PlayerResult::Loss => "L",
+            PlayerResult::Tie => "T",
+        };
+        write!(f, "{}", letter)
+    }
+}
+
+/// A completed game as written to the statistics file
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct GameRecord {
+    /// Seconds since the Unix epoch when the game was recorded
+    pub played_at: u64,
+    /// The seed the deck was shuffled with, if the game can be replayed from one
+    pub seed: Option<u64>,
+    pub config: GameConfig,
+    /// The players by seat
+    pub players: Vec<RecordedPlayer>,
+    pub outcome: EndGameCondition,
+    pub counts: TurnCounts,
+}
+
+impl GameRecord {
+    /// Record a finished game
+    ///
+    /// # Arguments
+    ///
+    /// * `standings` - The standings the game ended with
+    /// * `names` - The name of the player in each seat
+    /// * `seed` - The seed the game was shuffled with, if any
+    ///
+    /// # Panics
+    ///
+    /// If there is not exactly one name for every seat
+    pub fn from_game(
+        game: &GameState,
+        standings: &Standings,
+        names: &[String],
+        seed: Option<u64>,
+    ) -> Self {
+        assert_eq!(
+            names.len(),
+            game.players.len(),
+            "a game record needs one name for every seat"
+        );
+        let players = game
+            .players
+            .iter()
+            .zip(names)
+            .map(|(player, name)| RecordedPlayer {
+                name: name.clone(),
+                strategy: player.game_mode.to_string(),
+                score: player.score,
+                cards_in_hand: player.hand.len(),
+                successful_asks: player.successful_asks,
+                go_fishes: player.go_fishes,
+            })
+            .collect();
+        GameRecord {
+            played_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |elapsed| elapsed.as_secs()),
+            seed,
+            config: GameConfig::from_game(game),
+            players,
+            outcome: standings.outcome.clone(),
+            counts: TurnCounts::from_game(game),
+        }
+    }
+
+    /// Get how the game ended for the player in a seat
+    pub fn result(&self, player_index: usize) -> PlayerResult {
+        match &self.outcome {
+            EndGameCondition::Winner(winner) if *winner == player_index => PlayerResult::Win,
+            EndGameCondition::Tie(tied) if tied.contains(&player_index) => PlayerResult::Tie,
+            _ => PlayerResult::Loss,
+        }
+    }
+}
+
+/// Add a completed game to the end of a statistics file, one JSON record per line
+pub fn append_record(path: &Path, record: &GameRecord) -> Result<(), StatsError> {
+    let line = serde_json::to_string(record).map_err(io::Error::other)?;
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{}", line)?;
+    Ok(())
+}
+
+/// Read every game in a statistics file, none if the file does not exist yet
+pub fn load_records(path: &Path) -> Result<Vec<GameRecord>, StatsError> {
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(error) => return Err(error.into()),
+    };
+    contents
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| {
+            serde_json::from_str(line).map_err(|error| StatsError::Format(index + 1, error))
+        })
+        .collect()
+}
+
+/// The wins and losses of a player or strategy over the recorded games
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct RecordSummary {
+    pub games: u32,
+    pub wins: u32,
+    pub losses: u32,
+    pub ties: u32,
+    /// The result of the latest game and how many games in a row ended the same way
+    pub streak: Option<(PlayerResult, u32)>,
+    pub longest_winning_streak: u32,
+    pub longest_losing_streak: u32,
+    /// Turns played over all the games
+    pub turns: u64,
+}
+
+impl RecordSummary {
+    fn add(&mut self, result: PlayerResult, turns: u32) {
+        self.games += 1;
+        self.turns += u64::from(turns);
+        match result {
+            PlayerResult::Win => self.wins += 1,
+            PlayerResult::Loss => self.losses += 1,
+            PlayerResult::Tie => self.ties += 1,
+        }
+        let length = match self.streak {
+            Some((streak, length)) if streak == result => length + 1,
+            _ => 1,
+        };
+        self.streak = Some((result, length));
+        match result {
+            PlayerResult::Win => {
+                self.longest_winning_streak = self.longest_winning_streak.max(length)
+            }
+            PlayerResult::Loss => {
+                self.longest_losing_streak = self.longest_losing_streak.max(length)
+            }
+            PlayerResult::Tie => {}
+        }
+    }
+
+    /// Get the share of games won, 0 without games
+    pub fn win_rate(&self) -> f64 {
+        if self.games == 0 {
+            return 0.0;
+        }
+        f64::from(self.wins) / f64::from(self.games)
+    }
+
+    /// Get the average number of turns a game lasted, 0 without games
+    pub fn average_turns(&self) -> f64 {
+        if self.games == 0 {
+            return 0.0;
+        }
+        self.turns as f64 / f64::from(self.games)
+    }
+}
+
+/// Win and loss history by player name and by strategy over recorded games,
+/// in the order the games were played
+///
+/// # Example
+///
+/// ```
+/// use rust_go_fish::stats::{GameRecord, StatsReport};
+/// use rust_go_fish::{run_game, GameMode, GameState};
+///
+/// let mut game = GameState::new_seeded(vec![GameMode::Random, GameMode::Sequential], 5);
+/// game.verbose = false;
+/// game.deal();
+/// game.players_match_cards();
+/// let standings = run_game(&mut game);
+///
+/// let names = vec!["ann".to_string(), "bob".to_string()];
+/// let record = GameRecord::from_game(&game, &standings, &names, Some(5));
+/// let report = StatsReport::from_records(&[record]);
+/// assert_eq!(report.games, 1);
+/// assert_eq!(report.players["ann"].games, 1);
+/// assert_eq!(report.strategies["sequential"].games, 1);
+/// ```
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct StatsReport {
+    pub games: u32,
+    /// Turns played over all the games
+    pub turns: u64,
+    pub players: BTreeMap<String, RecordSummary>,
+    pub strategies: BTreeMap<String, RecordSummary>,
+}
+
+impl StatsReport {
+    pub fn from_records(records: &[GameRecord]) -> Self {
+        let mut report = StatsReport::default();
+        for record in records {
+            report.games += 1;
+            report.turns += u64::from(record.counts.turns);
+            for (player_index, player) in record.players.iter().enumerate() {
+                let result = record.result(player_index);
+                report
+                    .players
+                    .entry(player.name.clone())
+                    .or_default()
+                    .add(result, record.counts.turns);
+                report
+                    .strategies
+                    .entry(player.strategy.clone())
+                    .or_default()
+                    .add(result, record.counts.turns);
+            }
+        }
+        report
+    }
+
+    /// Get the average number of turns a game lasted, 0 without games
+    pub fn average_turns(&self) -> f64 {
+        if self.games == 0 {
+            return 0.0;
+        }
+        self.turns as f64 / f64::from(self.games)
+    }
+}
+
+/// Write a table of summaries, one line per name
+fn write_summaries(
+    f: &mut fmt::Formatter<'_>,
+    heading: &str,
+    summaries: &BTreeMap<String, RecordSummary>,
+) -> fmt::Result {
+    let name_width = summaries
+        .keys()
+        .map(|name| name.len())
+        .chain(std::iter::once(heading.len()))
+        .max()
+        .unwrap_or(0);
+    writeln!(
+        f,
+        "{:<name_width$}  {:>5}  {:>4}  {:>6}  {:>4}  {:>5}  {:>6}  {:>8}  {:>9}",
+        heading, "Games", "Wins", "Losses", "Ties", "Win %", "Streak", "Best run", "Avg turns"
+    )?;
+    for (name, summary) in summaries {
+        let streak = summary.streak.map_or(String::new(), |(result, length)| {
+            format!("{}{}", result, length)
+        });
+        writeln!(
+            f,
+            "{:<name_width$}  {:>5}  {:>4}  {:>6}  {:>4}  {:>5.1}  {:>6}  {:>8}  {:>9.1}",
+            name,
+            summary.games,
+            summary.wins,
+            summary.losses,
+            summary.ties,
+            summary.win_rate() * 100.0,
+            streak,
+            format!(
+                "W{} L{}",
+                summary.longest_winning_streak, summary.longest_losing_streak
+            ),
+            summary.average_turns()
+        )?;
+    }
+    Ok(())
+}
+
+impl fmt::Display for StatsReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{} games, {:.1} turns on average",
+            self.games,
+            self.average_turns()
+        )?;
+        writeln!(f)?;
+        write_summaries(f, "Player", &self.players)?;
+        writeln!(f)?;
+        write_summaries(f, "Strategy", &self.strategies)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{run_game, GameMode};
+
+    fn record(outcome: EndGameCondition, names: &[&str], turns: u32) -> GameRecord {
+        let mut game = GameState::new_seeded(vec![GameMode::Random; names.len()], 1);
+        game.turns = turns;
+        let names: Vec<String> = names.iter().map(|name| name.to_string()).collect();
+        let standings = Standings::from_game(outcome, None, &game);
+        GameRecord::from_game(&game, &standings, &names, None)
+    }
+
+    #[test]
+    fn test_record_counts_the_game() {
+        let mut game = GameState::new_seeded(vec![GameMode::Random, GameMode::Sequential], 7);
+        game.verbose = false;
+        game.deal();
+        game.players_match_cards();
+        let standings = run_game(&mut game);
+        let names = vec!["ann".to_string(), "bob".to_string()];
+        let record = GameRecord::from_game(&game, &standings, &names, Some(7));
+
+        assert_eq!(record.config.player_count, 2);
+        assert_eq!(record.config.deck_size, 52);
+        assert_eq!(record.players[1].strategy, "sequential");
+        assert_eq!(record.counts.turns, game.turns);
+        let successful_asks: u32 = game.players.iter().map(|p| p.successful_asks).sum();
+        let go_fishes: u32 = game.players.iter().map(|p| p.go_fishes).sum();
+        assert_eq!(record.counts.asks, successful_asks + go_fishes);
+        assert_eq!(record.counts.go_fishes, go_fishes);
+        let scores: u32 = game.players.iter().map(|p| p.score).sum();
+        assert_eq!(record.counts.books_laid_down, scores);
+        assert_eq!(record.outcome, standings.outcome);
+    }
+
+    #[test]
+    #[should_panic(expected = "one name for every seat")]
+    fn test_record_needs_a_name_for_every_seat() {
+        let game = GameState::new_seeded(vec![GameMode::Random; 3], 1);
+        let standings = Standings::from_game(EndGameCondition::Winner(0), None, &game);
+        GameRecord::from_game(&game, &standings, &["ann".to_string()], None);
+    }
+
+    #[test]
+    fn test_report_streaks() {
+        let names = ["ann", "bob"];
+        let records = vec![
+            record(EndGameCondition::Winner(0), &names, 10),
+            record(EndGameCondition::Winner(0), &names, 20),
+            record(EndGameCondition::Tie(vec![0, 1]), &names, 30),
+            record(EndGameCondition::Winner(1), &names, 40),
+        ];
+        let report = StatsReport::from_records(&records);
+        assert_eq!(report.games, 4);
+        assert_eq!(report.average_turns(), 25.0);
+
+        let ann = &report.players["ann"];
+        assert_eq!((ann.wins, ann.losses, ann.ties), (2, 1, 1));
+        assert_eq!(ann.streak, Some((PlayerResult::Loss, 1)));
+        assert_eq!(ann.longest_winning_streak, 2);
+        let bob = &report.players["bob"];
+        assert_eq!(bob.streak, Some((PlayerResult::Win, 1)));
+        assert_eq!(bob.longest_losing_streak, 2);
+
+        let random = &report.strategies["random"];
+        assert_eq!(random.games, 8);
+        assert_eq!(random.wins, 3);
+    }
+
+    #[test]
+    fn test_append_and_load() {
+        let path = std::env::temp_dir().join(format!("go_fish_stats_{}.jsonl", std::process::id()));
+        assert!(load_records(&path).unwrap().is_empty());
+        let first = record(EndGameCondition::Winner(1), &["ann", "bob"], 12);
+        let second = record(EndGameCondition::Tie(vec![0, 1]), &["ann", "cy"], 8);
+        append_record(&path, &first).unwrap();
+        append_record(&path, &second).unwrap();
+
+        let loaded = load_records(&path).unwrap();
+        fs::write(&path, "{}\n").unwrap();
+        let broken = load_records(&path);
+        fs::remove_file(&path).unwrap();
+        assert_eq!(loaded, vec![first, second]);
+        assert!(matches!(broken, Err(StatsError::Format(1, _))));
+    }
+}
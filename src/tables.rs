@@ -0,0 +1,200 @@
+use crate::audit::RuleViolation;
+use crate::game::{try_run_game, GameMode, GameState};
+use crate::nth_seed;
+use crate::rules::Rules;
+use crate::standings::Standings;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+/// A game to be played by the table manager, shuffled with its own seed
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Table {
+    /// How the player in each seat picks their asks
+    pub game_modes: Vec<GameMode>,
+    pub rules: Rules,
+    pub seed: u64,
+}
+
+impl Table {
+    pub fn new(game_modes: Vec<GameMode>, rules: Rules, seed: u64) -> Self {
+        Table {
+            game_modes,
+            rules,
+            seed,
+        }
+    }
+
+    /// Get a number of tables with the same players and rules, the first shuffled
+    /// with the given seed and every following table with the next seed, wrapping
+    /// around to 0 after `u64::MAX`
+    pub fn many(
+        game_modes: Vec<GameMode>,
+        rules: Rules,
+        first_seed: u64,
+        count: usize,
+    ) -> Vec<Self> {
+        (0..count as u64)
+            .map(|offset| {
+                Table::new(
+                    game_modes.clone(),
+                    rules.clone(),
+                    nth_seed(first_seed, offset),
+                )
+            })
+            .collect()
+    }
+
+    /// Deal and play the game to the end
+    pub fn play(&self) -> Result<TableResult, RuleViolation> {
+        let mut game = GameState::new_with_rules(
+            self.game_modes.clone(),
+            self.rules.clone(),
+            StdRng::seed_from_u64(self.seed),
+        );
+        game.verbose = false;
+        game.deal();
+        game.players_match_cards();
+        let standings = try_run_game(&mut game)?;
+        Ok(TableResult {
+            seed: self.seed,
+            turns: game.turns,
+            standings,
+        })
+    }
+}
+
+/// How a table's game ended
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct TableResult {
+    pub seed: u64,
+    pub turns: u32,
+    pub standings: Standings,
+}
+
+/// Plays many tables at once on a pool of threads
+///
+/// Every table is played start to finish on one thread with its own game
+/// state and random number generator, nothing is shared between tables, so
+/// a table ends the same way whatever the number of threads and whichever
+/// tables are played beside it. Threads take the next table waiting as soon
+/// as they finish one.
+///
+/// # Example
+///
+/// ```
+/// use rust_go_fish::tables::{Table, TableManager};
+/// use rust_go_fish::{GameMode, Rules};
+///
+/// let tables = Table::many(vec![GameMode::Random; 4], Rules::default(), 1, 8);
+/// let results = TableManager::new(2).run(&tables);
+/// assert_eq!(results.len(), 8);
+/// assert_eq!(results[3].as_ref().unwrap().seed, 4);
+/// ```
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct TableManager {
+    /// Number of threads playing tables
+    pub threads: usize,
+}
+
+impl Default for TableManager {
+    /// Use one thread per core the process may run on
+    fn default() -> Self {
+        let threads = thread::available_parallelism().map_or(1, NonZeroUsize::get);
+        TableManager::new(threads)
+    }
+}
+
+impl TableManager {
+    /// Create a manager playing on the given number of threads, at least one
+    pub fn new(threads: usize) -> Self {
+        TableManager {
+            threads: threads.max(1),
+        }
+    }
+
+    /// Play every table and get their results in the order of the tables
+    ///
+    /// A table whose game breaks a rule gets the violation as its result,
+    /// the other tables play on.
+    pub fn run(&self, tables: &[Table]) -> Vec<Result<TableResult, RuleViolation>> {
+        let next_table = AtomicUsize::new(0);
+        let mut results: Vec<Option<Result<TableResult, RuleViolation>>> = vec![None; tables.len()];
+        thread::scope(|scope| {
+            let workers: Vec<_> = (0..self.threads.min(tables.len()))
+                .map(|_| {
+                    scope.spawn(|| {
+                        let mut played = Vec::new();
+                        loop {
+                            let index = next_table.fetch_add(1, Ordering::Relaxed);
+                            let Some(table) = tables.get(index) else {
+                                return played;
+                            };
+                            played.push((index, table.play()));
+                        }
+                    })
+                })
+                .collect();
+            for worker in workers {
+                let played = worker
+                    .join()
+                    .unwrap_or_else(|panic| std::panic::resume_unwind(panic));
+                for (index, result) in played {
+                    results[index] = Some(result);
+                }
+            }
+        });
+        results
+            .into_iter()
+            .map(|result| result.expect("every table is played"))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search::SearchConfig;
+
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    #[test]
+    fn test_engine_can_be_shared_between_threads() {
+        assert_send_sync::<GameState>();
+        assert_send_sync::<GameMode>();
+        assert_send_sync::<SearchConfig>();
+        assert_send_sync::<Table>();
+        assert_send_sync::<TableResult>();
+        assert_send_sync::<TableManager>();
+    }
+
+    #[test]
+    fn test_results_do_not_depend_on_threads() {
+        let game_modes = vec![GameMode::Random, GameMode::Sequential, GameMode::Random];
+        let tables = Table::many(game_modes, Rules::default(), 10, 12);
+        let alone = TableManager::new(1).run(&tables);
+        let together = TableManager::new(4).run(&tables);
+        assert_eq!(alone, together);
+
+        let played: Vec<_> = tables.iter().map(Table::play).collect();
+        assert_eq!(together, played);
+        for (table, result) in tables.iter().zip(&together) {
+            assert_eq!(result.as_ref().unwrap().seed, table.seed);
+        }
+    }
+
+    #[test]
+    fn test_many_wraps_the_seed() {
+        let tables = Table::many(vec![GameMode::Random; 2], Rules::default(), u64::MAX, 2);
+        let seeds: Vec<u64> = tables.iter().map(|table| table.seed).collect();
+        assert_eq!(seeds, vec![u64::MAX, 0]);
+    }
+
+    #[test]
+    fn test_run_without_tables() {
+        assert!(TableManager::new(0).run(&[]).is_empty());
+        assert_eq!(TableManager::new(0).threads, 1);
+    }
+}
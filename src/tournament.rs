@@ -0,0 +1,565 @@
+use crate::game::{run_game, EndGameCondition, GameMode, GameState};
+use crate::nth_seed;
+use crate::rules::Rules;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use serde::Serialize;
+use std::fmt;
+use std::str::FromStr;
+
+/// z value for a 95% confidence interval
+const Z_95: f64 = 1.96;
+
+/// Most sudden death games played to separate a drawn elimination pairing
+/// before the better seeded entrant goes through
+pub const MAX_SUDDEN_DEATH_GAMES: u32 = 10;
+
+/// How entrants are paired up against each other
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Serialize)]
+pub enum Pairing {
+    /// Every entrant plays every other entrant
+    RoundRobin,
+    /// Single elimination bracket, the loser of each pairing is knocked out
+    Elimination,
+}
+
+impl FromStr for Pairing {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "round-robin" => Ok(Pairing::RoundRobin),
+            "elimination" => Ok(Pairing::Elimination),
+            _ => Err(format!("unknown pairing: {}", s)),
+        }
+    }
+}
+
+/// A named strategy taking part in the tournament
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Entrant {
+    pub name: String,
+    pub game_mode: GameMode,
+}
+
+impl Entrant {
+    pub fn new(name: &str, game_mode: GameMode) -> Self {
+        Entrant {
+            name: name.to_string(),
+            game_mode,
+        }
+    }
+}
+
+/// The settings for a tournament
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct TournamentConfig {
+    pub entrants: Vec<Entrant>,
+    pub pairing: Pairing,
+    /// Number of deals played for every pairing. Each deal is played twice
+    /// with the seats swapped so neither entrant profits from going first.
+    pub deals_per_pairing: u32,
+    /// Seed for the first game, every following game uses the next seed,
+    /// wrapping around to 0 after `u64::MAX`
+    pub seed: u64,
+    pub rules: Rules,
+}
+
+impl TournamentConfig {
+    /// Check the tournament can be played
+    pub fn validate(&self) -> Result<(), String> {
+        if self.entrants.len() < 2 {
+            return Err("a tournament needs at least 2 entrants".to_string());
+        }
+        if self.deals_per_pairing == 0 {
+            return Err("every pairing needs at least 1 deal".to_string());
+        }
+        for (position, entrant) in self.entrants.iter().enumerate() {
+            if self.entrants[..position]
+                .iter()
+                .any(|other| other.name == entrant.name)
+            {
+                return Err(format!("two entrants are named {}", entrant.name));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// An entrant's line on the leaderboard
+#[derive(Debug, PartialEq, Clone, Serialize)]
+pub struct LeaderboardEntry {
+    pub name: String,
+    pub games: u32,
+    pub wins: u32,
+    pub ties: u32,
+    pub losses: u32,
+    /// Elimination bracket rounds the entrant went through, by winning or on a bye
+    pub rounds_advanced: u32,
+    /// Share of points won, a tie counts as half a win
+    pub score: f64,
+    /// Lower bound of the 95% confidence interval of the score
+    pub score_low: f64,
+    /// Upper bound of the 95% confidence interval of the score
+    pub score_high: f64,
+}
+
+impl LeaderboardEntry {
+    fn new(name: &str) -> Self {
+        LeaderboardEntry {
+            name: name.to_string(),
+            games: 0,
+            wins: 0,
+            ties: 0,
+            losses: 0,
+            rounds_advanced: 0,
+            score: 0.0,
+            score_low: 0.0,
+            score_high: 0.0,
+        }
+    }
+
+    /// Points won, a tie counts as half a win
+    fn points(&self) -> f64 {
+        self.wins as f64 + self.ties as f64 / 2.0
+    }
+
+    /// Update the score and its Wilson score confidence interval
+    fn update_score(&mut self) {
+        if self.games == 0 {
+            return;
+        }
+        let n = self.games as f64;
+        let p = self.points() / n;
+        let z2 = Z_95 * Z_95;
+        let center = (p + z2 / (2.0 * n)) / (1.0 + z2 / n);
+        let margin = Z_95 / (1.0 + z2 / n) * (p * (1.0 - p) / n + z2 / (4.0 * n * n)).sqrt();
+        self.score = p;
+        self.score_low = (center - margin).max(0.0);
+        self.score_high = (center + margin).min(1.0);
+    }
+}
+
+/// A single game played in the tournament
+#[derive(Debug, PartialEq, Eq, Clone, Serialize)]
+pub struct TournamentGame {
+    pub seed: u64,
+    /// Name of the entrant in each seat
+    pub seats: Vec<String>,
+    pub outcome: EndGameCondition,
+}
+
+/// An elimination pairing that ended level on points and how it was decided
+#[derive(Debug, PartialEq, Eq, Clone, Serialize)]
+pub struct DrawnPairing {
+    /// Names of the entrants, the better seed first
+    pub entrants: [String; 2],
+    /// Single games played, alternating the first seat, until one was won
+    pub sudden_death_games: u32,
+    /// Name of the entrant who went through
+    pub winner: String,
+    /// No sudden death game was won, so the better seed went through
+    pub by_seed: bool,
+}
+
+/// The final leaderboard of a tournament, best entrant first,
+/// and every game that was played
+#[derive(Debug, PartialEq, Clone, Serialize)]
+pub struct TournamentResult {
+    pub pairing: Pairing,
+    pub leaderboard: Vec<LeaderboardEntry>,
+    pub games: Vec<TournamentGame>,
+    /// Elimination pairings that had to be decided after a draw
+    pub drawn_pairings: Vec<DrawnPairing>,
+}
+
+impl TournamentResult {
+    /// Serialize the result as pretty printed JSON
+    pub fn to_json(&self) -> String {
+        // Serializing plain structs, enums and numbers can not fail
+        serde_json::to_string_pretty(self).unwrap()
+    }
+}
+
+/// Runs many seeded heads up games between strategies and ranks them
+pub struct Tournament {
+    pub config: TournamentConfig,
+    leaderboard: Vec<LeaderboardEntry>,
+    games: Vec<TournamentGame>,
+    drawn_pairings: Vec<DrawnPairing>,
+    next_seed: u64,
+}
+
+impl Tournament {
+    pub fn new(config: TournamentConfig) -> Self {
+        let leaderboard = config
+            .entrants
+            .iter()
+            .map(|entrant| LeaderboardEntry::new(&entrant.name))
+            .collect();
+        let next_seed = config.seed;
+        Tournament {
+            config,
+            leaderboard,
+            games: Vec::new(),
+            drawn_pairings: Vec::new(),
+            next_seed,
+        }
+    }
+
+    /// Play every pairing and return the leaderboard
+    pub fn run(mut self) -> TournamentResult {
+        match self.config.pairing {
+            Pairing::RoundRobin => self.run_round_robin(),
+            Pairing::Elimination => self.run_elimination(),
+        }
+
+        for entry in &mut self.leaderboard {
+            entry.update_score();
+        }
+        let mut leaderboard = self.leaderboard;
+        leaderboard.sort_by(|a, b| {
+            b.rounds_advanced
+                .cmp(&a.rounds_advanced)
+                .then(b.score.total_cmp(&a.score))
+        });
+        TournamentResult {
+            pairing: self.config.pairing,
+            leaderboard,
+            games: self.games,
+            drawn_pairings: self.drawn_pairings,
+        }
+    }
+
+    fn run_round_robin(&mut self) {
+        let entrant_count = self.config.entrants.len();
+        for first in 0..entrant_count {
+            for second in first + 1..entrant_count {
+                self.play_pairing(first, second);
+            }
+        }
+    }
+
+    /// Play the bracket, the best seeded entrant gets a bye when the field is odd
+    fn run_elimination(&mut self) {
+        let mut remaining: Vec<usize> = (0..self.config.entrants.len()).collect();
+        while remaining.len() > 1 {
+            let mut next_round = Vec::new();
+            if remaining.len() % 2 == 1 {
+                let bye = remaining.remove(0);
+                self.leaderboard[bye].rounds_advanced += 1;
+                next_round.push(bye);
+            }
+            let half = remaining.len() / 2;
+            for position in 0..half {
+                // best remaining seed plays the worst remaining seed
+                let first = remaining[position];
+                let second = remaining[remaining.len() - 1 - position];
+                let (first_points, second_points) = self.play_pairing(first, second);
+                let winner = if first_points > second_points {
+                    first
+                } else if second_points > first_points {
+                    second
+                } else {
+                    self.play_sudden_death(first, second)
+                };
+                self.leaderboard[winner].rounds_advanced += 1;
+                next_round.push(winner);
+            }
+            next_round.sort();
+            remaining = next_round;
+        }
+    }
+
+    /// Play all deals of a pairing, each deal once from either seat,
+    /// and return the points won by each entrant
+    fn play_pairing(&mut self, first: usize, second: usize) -> (f64, f64) {
+        let mut first_points = 0.0;
+        let mut second_points = 0.0;
+        for _ in 0..self.config.deals_per_pairing {
+            let seed = self.next_seed;
+            self.next_seed = nth_seed(self.next_seed, 1);
+            for seats in [[first, second], [second, first]] {
+                let outcome = self.play_game(seats, seed);
+                let points = self.record(seats, &outcome);
+                self.games.push(TournamentGame {
+                    seed,
+                    seats: seats
+                        .iter()
+                        .map(|&entrant| self.config.entrants[entrant].name.clone())
+                        .collect(),
+                    outcome,
+                });
+                if seats[0] == first {
+                    first_points += points[0];
+                    second_points += points[1];
+                } else {
+                    first_points += points[1];
+                    second_points += points[0];
+                }
+            }
+        }
+        (first_points, second_points)
+    }
+
+    /// Break a drawn pairing with single games, alternating who sits first, until
+    /// one of them is won, and return the entrant who goes through
+    ///
+    /// The better seed goes through if `MAX_SUDDEN_DEATH_GAMES` games are not enough.
+    fn play_sudden_death(&mut self, first: usize, second: usize) -> usize {
+        let mut winner = None;
+        let mut games = 0;
+        while winner.is_none() && games < MAX_SUDDEN_DEATH_GAMES {
+            let seats = if games % 2 == 0 {
+                [first, second]
+            } else {
+                [second, first]
+            };
+            let seed = self.next_seed;
+            self.next_seed = nth_seed(self.next_seed, 1);
+            games += 1;
+            let outcome = self.play_game(seats, seed);
+            self.record(seats, &outcome);
+            if let EndGameCondition::Winner(seat) = outcome {
+                winner = Some(seats[seat]);
+            }
+            self.games.push(TournamentGame {
+                seed,
+                seats: seats
+                    .iter()
+                    .map(|&entrant| self.config.entrants[entrant].name.clone())
+                    .collect(),
+                outcome,
+            });
+        }
+        let name = |entrant: usize| self.config.entrants[entrant].name.clone();
+        self.drawn_pairings.push(DrawnPairing {
+            entrants: [name(first), name(second)],
+            sudden_death_games: games,
+            winner: name(winner.unwrap_or(first)),
+            by_seed: winner.is_none(),
+        });
+        winner.unwrap_or(first)
+    }
+
+    /// Play a single seeded game with the entrants in the given seats
+    fn play_game(&self, seats: [usize; 2], seed: u64) -> EndGameCondition {
+        let game_modes = seats
+            .iter()
+            .map(|&entrant| self.config.entrants[entrant].game_mode)
+            .collect();
+        let rules = self.config.rules.clone();
+        let mut game = GameState::new_with_rules(game_modes, rules, StdRng::seed_from_u64(seed));
+        game.verbose = false;
+        game.deal();
+        game.players_match_cards();
+        run_game(&mut game).outcome
+    }
+
+    /// Record the outcome on the leaderboard and return the points won by each seat
+    fn record(&mut self, seats: [usize; 2], outcome: &EndGameCondition) -> [f64; 2] {
+        let mut points = [0.0; 2];
+        for (seat, &entrant) in seats.iter().enumerate() {
+            let entry = &mut self.leaderboard[entrant];
+            entry.games += 1;
+            match outcome {
+                EndGameCondition::Winner(winner) if *winner == seat => {
+                    entry.wins += 1;
+                    points[seat] = 1.0;
+                }
+                EndGameCondition::Tie(tied) if tied.contains(&seat) => {
+                    entry.ties += 1;
+                    points[seat] = 0.5;
+                }
+                _ => entry.losses += 1,
+            }
+        }
+        points
+    }
+}
+
+impl fmt::Display for TournamentResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name_width = self
+            .leaderboard
+            .iter()
+            .map(|entry| entry.name.len())
+            .max()
+            .unwrap_or(0)
+            .max(4);
+        writeln!(
+            f,
+            "{:>4}  {:<name_width$}  {:>5}  {:>5}  {:>5}  {:>6}  {:>6}  {:>15}",
+            "Rank", "Name", "Games", "Wins", "Ties", "Losses", "Score", "95% CI"
+        )?;
+        for (position, entry) in self.leaderboard.iter().enumerate() {
+            writeln!(
+                f,
+                "{:>4}  {:<name_width$}  {:>5}  {:>5}  {:>5}  {:>6}  {:>6.3}  {:>15}",
+                position + 1,
+                entry.name,
+                entry.games,
+                entry.wins,
+                entry.ties,
+                entry.losses,
+                entry.score,
+                format!("[{:.3}, {:.3}]", entry.score_low, entry.score_high)
+            )?;
+        }
+        for drawn in &self.drawn_pairings {
+            write!(f, "{} and {} drew, ", drawn.entrants[0], drawn.entrants[1])?;
+            if drawn.by_seed {
+                writeln!(
+                    f,
+                    "{} went through as the better seed after {} sudden death games",
+                    drawn.winner, drawn.sudden_death_games
+                )?;
+            } else {
+                writeln!(
+                    f,
+                    "{} won sudden death game {}",
+                    drawn.winner, drawn.sudden_death_games
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(pairing: Pairing, entrants: Vec<Entrant>) -> TournamentConfig {
+        TournamentConfig {
+            entrants,
+            pairing,
+            deals_per_pairing: 5,
+            seed: 1,
+            rules: Rules::default(),
+        }
+    }
+
+    #[test]
+    fn test_round_robin_plays_every_pairing_from_both_seats() {
+        let entrants = vec![
+            Entrant::new("random-a", GameMode::Random),
+            Entrant::new("sequential", GameMode::Sequential),
+            Entrant::new("random-b", GameMode::Random),
+        ];
+        let result = Tournament::new(config(Pairing::RoundRobin, entrants)).run();
+
+        assert_eq!(result.leaderboard.len(), 3);
+        for entry in &result.leaderboard {
+            // two opponents, five deals, both seats
+            assert_eq!(entry.games, 20);
+            assert_eq!(entry.wins + entry.ties + entry.losses, entry.games);
+            assert!(entry.score_low <= entry.score && entry.score <= entry.score_high);
+        }
+        // three pairings, five deals, both seats
+        assert_eq!(result.games.len(), 30);
+        assert_eq!(result.games[0].seats, vec!["random-a", "sequential"]);
+        assert_eq!(result.games[1].seats, vec!["sequential", "random-a"]);
+        assert_eq!(result.games[0].seed, result.games[1].seed);
+        let scores: Vec<f64> = result.leaderboard.iter().map(|entry| entry.score).collect();
+        assert!(scores.windows(2).all(|pair| pair[0] >= pair[1]));
+    }
+
+    #[test]
+    fn test_seed_wraps_around() {
+        let entrants = vec![
+            Entrant::new("random-a", GameMode::Random),
+            Entrant::new("random-b", GameMode::Random),
+        ];
+        let mut config = config(Pairing::Elimination, entrants);
+        config.seed = u64::MAX;
+        let result = Tournament::new(config).run();
+
+        assert_eq!(result.games[0].seed, u64::MAX);
+        assert_eq!(result.games[2].seed, 0);
+        let seeds: Vec<u64> = result.games.iter().map(|game| game.seed).collect();
+        // five deals and at most ten sudden death games, all but the first after the wrap
+        assert!(seeds.iter().all(|&seed| seed == u64::MAX || seed < 15));
+    }
+
+    #[test]
+    fn test_round_robin_is_repeatable() {
+        let entrants = vec![
+            Entrant::new("random", GameMode::Random),
+            Entrant::new("sequential", GameMode::Sequential),
+        ];
+        let first = Tournament::new(config(Pairing::RoundRobin, entrants.clone())).run();
+        let second = Tournament::new(config(Pairing::RoundRobin, entrants)).run();
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn test_elimination_crowns_a_single_champion() {
+        let entrants = (0..5)
+            .map(|index| Entrant::new(&format!("random-{}", index), GameMode::Random))
+            .collect();
+        let result = Tournament::new(config(Pairing::Elimination, entrants)).run();
+
+        // five entrants need three bracket rounds
+        assert_eq!(result.leaderboard[0].rounds_advanced, 3);
+        assert!(result.leaderboard[1..]
+            .iter()
+            .all(|entry| entry.rounds_advanced < 3));
+    }
+
+    #[test]
+    fn test_elimination_breaks_a_draw_with_sudden_death() {
+        // the same strategy from both seats of every deal wins one game each
+        let entrants = vec![
+            Entrant::new("sequential-a", GameMode::Sequential),
+            Entrant::new("sequential-b", GameMode::Sequential),
+        ];
+        let result = Tournament::new(config(Pairing::Elimination, entrants)).run();
+
+        assert_eq!(result.drawn_pairings.len(), 1);
+        let drawn = &result.drawn_pairings[0];
+        assert_eq!(drawn.entrants, ["sequential-a", "sequential-b"]);
+        assert!(drawn.sudden_death_games >= 1);
+        assert_eq!(result.leaderboard[0].name, drawn.winner);
+        assert_eq!(result.leaderboard[0].rounds_advanced, 1);
+        assert_eq!(result.games.len() as u32, 10 + drawn.sudden_death_games);
+        assert!(result
+            .to_string()
+            .contains("sequential-a and sequential-b drew"));
+    }
+
+    #[test]
+    fn test_config_validate() {
+        let entrants = vec![
+            Entrant::new("random", GameMode::Random),
+            Entrant::new("sequential", GameMode::Sequential),
+        ];
+        let mut config = config(Pairing::Elimination, entrants);
+        assert_eq!(config.validate(), Ok(()));
+        config.deals_per_pairing = 0;
+        assert!(config.validate().is_err());
+        config.deals_per_pairing = 1;
+        config.entrants[1].name = "random".to_string();
+        assert_eq!(
+            config.validate(),
+            Err("two entrants are named random".to_string())
+        );
+        config.entrants.truncate(1);
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_confidence_interval_narrows_with_more_games() {
+        let mut few = LeaderboardEntry::new("few");
+        few.games = 10;
+        few.wins = 5;
+        few.update_score();
+        let mut many = LeaderboardEntry::new("many");
+        many.games = 1000;
+        many.wins = 500;
+        many.update_score();
+
+        assert!((few.score - 0.5).abs() < f64::EPSILON);
+        assert!(many.score_high - many.score_low < few.score_high - few.score_low);
+    }
+}
@@ -0,0 +1,459 @@
+use crate::deck::{pairing_rank, DeckConfig};
+use crate::game::{GameEvent, GameState};
+use crate::rules::Rules;
+use cardpack::Card;
+
+/// Most rounds of balancing the estimate against the hand sizes and the unseen cards
+const MAX_BALANCING_ROUNDS: usize = 1000;
+
+/// Largest difference from the unseen count of a rank left in a balanced estimate
+const BALANCED: f64 = 1e-9;
+
+/// Get one card of every rank in a deck, in deck order, with the jokers counted
+/// as one rank since they pair with each other
+pub fn ranks_in_play(deck: &DeckConfig) -> Vec<Card> {
+    let mut ranks: Vec<Card> = Vec::new();
+    for card in deck.build().cards() {
+        if !ranks
+            .iter()
+            .any(|other| pairing_rank(other) == pairing_rank(card))
+        {
+            ranks.push(card.clone());
+        }
+    }
+    ranks
+}
+
+/// Keeps track of where the cards a player has not seen are likely to be, from
+/// public information only
+///
+/// The tracker is fed the events of a game as they happen. It counts the cards
+/// in every hand and the stock, and for every other player the cards of each
+/// rank they are known to hold, from their asks and the cards handed to them,
+/// and the ranks they are known not to hold, from answering go fish until they
+/// draw again. The cards other players draw are never looked at. The viewer is
+/// the player whose hand is known, or nobody for a spectator.
+///
+/// The remaining cards are spread over the hands and the stock in proportion
+/// to the room left in each, balanced so every hand gets as many cards as it
+/// holds and every rank as many as are unseen. Cards of a rank are not told
+/// apart, so every unseen card of a rank has the same odds.
+///
+/// # Example
+///
+/// ```
+/// use rust_go_fish::tracker::CardTracker;
+/// use rust_go_fish::{GameMode, GameState};
+///
+/// let mut game = GameState::new_seeded(vec![GameMode::Random; 3], 1);
+/// game.verbose = false;
+/// game.deal();
+/// game.players_match_cards();
+///
+/// let tracker = CardTracker::from_game(&game, Some(0));
+/// let card = game.deck.cards()[0].clone();
+/// let odds = tracker.card_locations(&card);
+/// // the viewer knows the card is not in their hand
+/// assert_eq!(odds[0], 0.0);
+/// assert!((odds.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+/// ```
+#[derive(Debug, Clone)]
+pub struct CardTracker {
+    /// The player whose hand is known, none for a spectator
+    pub viewer: Option<usize>,
+    /// One card of every rank in play, the order of the ranks in every table
+    pub ranks: Vec<Card>,
+    /// Number of cards in every hand, by player index
+    pub hand_sizes: Vec<usize>,
+    pub stock_size: usize,
+    /// Number of cards of each rank in the deck
+    totals: Vec<usize>,
+    /// Number of cards of each rank laid down
+    laid_down: Vec<usize>,
+    /// The viewer's cards of each rank
+    viewer_hand: Vec<usize>,
+    /// Cards of each rank every player is known to hold, by player index
+    known: Vec<Vec<usize>>,
+    /// Ranks every player is known not to hold, by player index
+    excluded: Vec<Vec<bool>>,
+    /// The player asked and the rank asked for by the last ask
+    last_ask: Option<(usize, usize)>,
+    /// Asks are for exact cards in Happy Families, so go fish rules out no rank
+    exact_asks: bool,
+}
+
+impl CardTracker {
+    /// Start tracking a game before the deal, with every card in the stock
+    pub fn new(rules: &Rules, player_count: usize, viewer: Option<usize>) -> Self {
+        let ranks = ranks_in_play(&rules.deck);
+        let mut totals = vec![0; ranks.len()];
+        let deck = rules.deck.build();
+        for card in deck.cards() {
+            if let Some(slot) = rank_slot(&ranks, card) {
+                totals[slot] += 1;
+            }
+        }
+        CardTracker {
+            viewer,
+            hand_sizes: vec![0; player_count],
+            stock_size: deck.len(),
+            laid_down: vec![0; ranks.len()],
+            viewer_hand: vec![0; ranks.len()],
+            known: vec![vec![0; ranks.len()]; player_count],
+            excluded: vec![vec![false; ranks.len()]; player_count],
+            last_ask: None,
+            exact_asks: rules.families.is_some(),
+            totals,
+            ranks,
+        }
+    }
+
+    /// Track a game from its recorded events, the game must have been dealt with `deal`
+    pub fn from_game(game: &GameState, viewer: Option<usize>) -> Self {
+        let mut tracker = CardTracker::new(&game.rules, game.player_count, viewer);
+        for event in &game.events {
+            tracker.observe(event);
+        }
+        tracker
+    }
+
+    /// Update the tracker with the public part of an event
+    pub fn observe(&mut self, event: &GameEvent) {
+        match event {
+            GameEvent::Dealt {
+                player_index,
+                cards,
+            } => {
+                self.stock_size = self.stock_size.saturating_sub(cards.len());
+                for card in cards {
+                    self.receive(*player_index, card, false);
+                }
+            }
+            GameEvent::Asked { asker, asked, card } => {
+                if let Some(slot) = rank_slot(&self.ranks, card) {
+                    self.last_ask = Some((*asked, slot));
+                    if Some(*asker) != self.viewer {
+                        self.known[*asker][slot] = self.known[*asker][slot].max(1);
+                        self.excluded[*asker][slot] = false;
+                    }
+                }
+            }
+            GameEvent::HandedOver { from, to, cards } => {
+                for card in cards {
+                    self.give_up(*from, card);
+                    self.receive(*to, card, true);
+                }
+            }
+            GameEvent::GoFish { .. } => {
+                if let (Some((asked, slot)), false) = (self.last_ask, self.exact_asks) {
+                    if Some(asked) != self.viewer {
+                        self.known[asked][slot] = 0;
+                        self.excluded[asked][slot] = true;
+                    }
+                }
+            }
+            GameEvent::Drew { player_index, card } => {
+                self.stock_size = self.stock_size.saturating_sub(1);
+                self.receive(*player_index, card, false);
+            }
+            GameEvent::LaidDown {
+                player_index,
+                cards,
+            } => {
+                for card in cards {
+                    self.give_up(*player_index, card);
+                    if let Some(slot) = rank_slot(&self.ranks, card) {
+                        self.laid_down[slot] += 1;
+                    }
+                }
+            }
+            GameEvent::SatOut { .. } => {}
+        }
+    }
+
+    /// Add a card to a hand, looking at it only if the viewer holds it or it was
+    /// shown when handed over
+    fn receive(&mut self, player_index: usize, card: &Card, shown: bool) {
+        self.hand_sizes[player_index] += 1;
+        let slot = rank_slot(&self.ranks, card);
+        if Some(player_index) == self.viewer {
+            if let Some(slot) = slot {
+                self.viewer_hand[slot] += 1;
+            }
+        } else if let (Some(slot), true) = (slot, shown) {
+            self.known[player_index][slot] += 1;
+            self.excluded[player_index][slot] = false;
+        } else if !shown {
+            // an unseen card may be of any rank
+            self.excluded[player_index]
+                .iter_mut()
+                .for_each(|excluded| *excluded = false);
+        }
+    }
+
+    /// Take a shown card out of a hand
+    fn give_up(&mut self, player_index: usize, card: &Card) {
+        self.hand_sizes[player_index] = self.hand_sizes[player_index].saturating_sub(1);
+        if let Some(slot) = rank_slot(&self.ranks, card) {
+            if Some(player_index) == self.viewer {
+                self.viewer_hand[slot] = self.viewer_hand[slot].saturating_sub(1);
+            } else {
+                self.known[player_index][slot] = self.known[player_index][slot].saturating_sub(1);
+            }
+        }
+    }
+
+    /// Get the number of cards of each rank the viewer has not seen
+    pub fn unseen(&self) -> Vec<usize> {
+        (0..self.ranks.len())
+            .map(|slot| {
+                self.totals[slot].saturating_sub(self.laid_down[slot] + self.viewer_hand[slot])
+            })
+            .collect()
+    }
+
+    /// Get the expected number of unseen cards of each rank in every hand and
+    /// the stock, by player index with the stock last, then by rank
+    ///
+    /// The viewer's row is all zeros, their cards are not unseen.
+    pub fn expected_counts(&self) -> Vec<Vec<f64>> {
+        let player_count = self.hand_sizes.len();
+        let rank_count = self.ranks.len();
+        let hidden: Vec<usize> = (0..player_count)
+            .filter(|&index| Some(index) != self.viewer)
+            .collect();
+
+        // cards of every rank not placed by what is known, and the room left for them
+        let mut unplaced: Vec<f64> = self.unseen().iter().map(|&count| count as f64).collect();
+        let mut room = vec![0.0; player_count + 1];
+        for &index in &hidden {
+            let known: usize = self.known[index].iter().sum();
+            room[index] = self.hand_sizes[index].saturating_sub(known) as f64;
+            for (count, &known) in unplaced.iter_mut().zip(&self.known[index]) {
+                *count = (*count - known as f64).max(0.0);
+            }
+        }
+        room[player_count] = self.stock_size as f64;
+
+        // spread the unplaced cards where they may be, then balance the spread
+        // so every location is filled and every rank used up
+        let mut spread = vec![vec![0.0; rank_count]; player_count + 1];
+        for location in hidden.iter().copied().chain([player_count]) {
+            for slot in 0..rank_count {
+                let excluded = location < player_count && self.excluded[location][slot];
+                if !excluded && room[location] > 0.0 && unplaced[slot] > 0.0 {
+                    spread[location][slot] = 1.0;
+                }
+            }
+        }
+        for _ in 0..MAX_BALANCING_ROUNDS {
+            let mut largest_change: f64 = 0.0;
+            for slot in 0..rank_count {
+                let total: f64 = spread.iter().map(|row| row[slot]).sum();
+                if total > 0.0 {
+                    largest_change = largest_change.max((unplaced[slot] - total).abs());
+                    spread
+                        .iter_mut()
+                        .for_each(|row| row[slot] *= unplaced[slot] / total);
+                }
+            }
+            // the hands are balanced last so they always add up to their size
+            for (location, row) in spread.iter_mut().enumerate() {
+                let total: f64 = row.iter().sum();
+                if total > 0.0 {
+                    row.iter_mut()
+                        .for_each(|value| *value *= room[location] / total);
+                }
+            }
+            if largest_change < BALANCED {
+                break;
+            }
+        }
+
+        for &index in &hidden {
+            for (value, &known) in spread[index].iter_mut().zip(&self.known[index]) {
+                *value += known as f64;
+            }
+        }
+        spread
+    }
+
+    /// Get the probability that an unseen card of the given card's rank is in
+    /// each hand or the stock, by player index with the stock last
+    ///
+    /// The probabilities are all zero when every card of the rank has been seen.
+    pub fn card_locations(&self, card: &Card) -> Vec<f64> {
+        let expected = self.expected_counts();
+        let slot = match rank_slot(&self.ranks, card) {
+            Some(slot) => slot,
+            None => return vec![0.0; expected.len()],
+        };
+        let total: f64 = expected.iter().map(|row| row[slot]).sum();
+        expected
+            .iter()
+            .map(|row| if total > 0.0 { row[slot] / total } else { 0.0 })
+            .collect()
+    }
+
+    /// Get the probability that every player holds at least one card of each
+    /// rank, by player index and then by rank
+    ///
+    /// The viewer's row is one for the ranks in their hand and zero otherwise.
+    pub fn holding_probabilities(&self) -> Vec<Vec<f64>> {
+        let expected = self.expected_counts();
+        let unseen = self.unseen();
+        (0..self.hand_sizes.len())
+            .map(|index| {
+                (0..self.ranks.len())
+                    .map(|slot| {
+                        if Some(index) == self.viewer {
+                            return if self.viewer_hand[slot] > 0 { 1.0 } else { 0.0 };
+                        }
+                        if self.known[index][slot] > 0 {
+                            return 1.0;
+                        }
+                        // the chance that none of the unplaced cards of the rank is here
+                        let unplaced = unseen[slot].saturating_sub(
+                            self.known.iter().map(|known| known[slot]).sum::<usize>(),
+                        );
+                        if unplaced == 0 {
+                            return 0.0;
+                        }
+                        let share = (expected[index][slot] / unplaced as f64).min(1.0);
+                        1.0 - (1.0 - share).powi(unplaced as i32)
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+/// Get the position of a card's rank in a list of ranks
+fn rank_slot(ranks: &[Card], card: &Card) -> Option<usize> {
+    ranks
+        .iter()
+        .position(|rank| pairing_rank(rank) == pairing_rank(card))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::deck::parse_card;
+    use crate::game::GameMode;
+    use crate::scenario::Scenario;
+
+    fn card(index: &str) -> Card {
+        parse_card(index).unwrap()
+    }
+
+    fn dealt(tracker: &mut CardTracker, player_index: usize, hand: &str) {
+        let cards = hand.split_whitespace().map(card).collect();
+        tracker.observe(&GameEvent::Dealt {
+            player_index,
+            cards,
+        });
+    }
+
+    fn setup() -> CardTracker {
+        let mut tracker = CardTracker::new(&Rules::default(), 3, Some(0));
+        dealt(&mut tracker, 0, "7C 7D 7H 9S");
+        dealt(&mut tracker, 1, "2C 3C 4C 5C");
+        dealt(&mut tracker, 2, "2D 3D 4D 5D");
+        tracker
+    }
+
+    fn slot(tracker: &CardTracker, index: &str) -> usize {
+        rank_slot(&tracker.ranks, &card(index)).unwrap()
+    }
+
+    #[test]
+    fn test_deal_spreads_unseen_cards_by_hand_size() {
+        let tracker = setup();
+        assert_eq!(tracker.stock_size, 40);
+        let expected = tracker.expected_counts();
+        for (location, size) in [(1, 4.0), (2, 4.0), (3, 40.0)] {
+            let total: f64 = expected[location].iter().sum();
+            assert!((total - size).abs() < 1e-6);
+        }
+        assert!(expected[0].iter().all(|&value| value == 0.0));
+
+        // the last seven is as likely in any of the 48 unseen places
+        let odds = tracker.card_locations(&card("7S"));
+        assert!((odds[1] - 4.0 / 48.0).abs() < 1e-6);
+        assert!((odds[3] - 40.0 / 48.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_ask_and_go_fish_place_ranks() {
+        let mut tracker = setup();
+        // player 1 asks player 2 for twos and goes fish
+        tracker.observe(&GameEvent::Asked {
+            asker: 1,
+            asked: 2,
+            card: card("2C"),
+        });
+        tracker.observe(&GameEvent::GoFish { player_index: 1 });
+        tracker.observe(&GameEvent::Drew {
+            player_index: 1,
+            card: card("KH"),
+        });
+        let two = slot(&tracker, "2C");
+        let holding = tracker.holding_probabilities();
+        assert_eq!(holding[1][two], 1.0);
+        assert_eq!(holding[2][two], 0.0);
+        assert_eq!(holding[0][slot(&tracker, "7C")], 1.0);
+        assert_eq!(tracker.hand_sizes[1], 5);
+
+        // the card player 1 drew was never looked at
+        let king = slot(&tracker, "KH");
+        assert_eq!(tracker.known[1][king], 0);
+
+        // once player 2 draws they may hold a two again
+        tracker.observe(&GameEvent::Drew {
+            player_index: 2,
+            card: card("QH"),
+        });
+        assert!(tracker.holding_probabilities()[2][two] > 0.0);
+    }
+
+    #[test]
+    fn test_handed_over_and_laid_down_cards() {
+        let mut tracker = setup();
+        tracker.observe(&GameEvent::Asked {
+            asker: 0,
+            asked: 1,
+            card: card("7C"),
+        });
+        tracker.observe(&GameEvent::HandedOver {
+            from: 1,
+            to: 0,
+            cards: vec![card("7S")],
+        });
+        tracker.observe(&GameEvent::LaidDown {
+            player_index: 0,
+            cards: vec![card("7C"), card("7S")],
+        });
+        let seven = slot(&tracker, "7C");
+        assert_eq!(tracker.unseen()[seven], 0);
+        assert!(tracker
+            .card_locations(&card("7C"))
+            .iter()
+            .all(|&odds| odds == 0.0));
+        assert_eq!(tracker.hand_sizes, vec![3, 3, 4]);
+    }
+
+    #[test]
+    fn test_spectator_sees_no_hand() {
+        let mut game = Scenario::new(Rules::default())
+            .player(GameMode::Random, "")
+            .player(GameMode::Random, "")
+            .build()
+            .unwrap();
+        game.deal();
+        let tracker = CardTracker::from_game(&game, None);
+        let expected = tracker.expected_counts();
+        let hands: f64 = expected[0].iter().chain(&expected[1]).sum();
+        assert!((hands - 14.0).abs() < 1e-6);
+        assert_eq!(tracker.unseen().iter().sum::<usize>(), 52);
+    }
+}
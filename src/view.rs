@@ -0,0 +1,241 @@
+use crate::deck::pairing_rank;
+use crate::game::{AskRecord, GameMode, GameState};
+use crate::rules::Rules;
+use cardpack::{Card, Pile, Rank};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+
+/// What a single player can see of a game in progress: their own hand and
+/// everything that happened in public, but not the other hands or the stock
+#[derive(Debug, Clone)]
+pub struct PlayerView {
+    /// The player seeing the game
+    pub player_index: usize,
+    pub hand: Pile,
+    /// Number of cards in every player's hand, by player index
+    pub hand_sizes: Vec<usize>,
+    /// The cards every player laid down, by player index
+    pub pairs: Vec<Pile>,
+    pub scores: Vec<u32>,
+    /// Number of cards left in the stock
+    pub deck_size: usize,
+    pub asks: Vec<AskRecord>,
+    pub went_out: Vec<usize>,
+    pub dealer_index: usize,
+    pub turns: u32,
+    pub game_modes: Vec<GameMode>,
+    pub rules: Rules,
+}
+
+impl PlayerView {
+    /// Get what a player can see of a game
+    pub fn from_game(game: &GameState, player_index: usize) -> Self {
+        PlayerView {
+            player_index,
+            hand: game.players[player_index].hand.to_pile(),
+            hand_sizes: game
+                .players
+                .iter()
+                .map(|player| player.hand.len())
+                .collect(),
+            pairs: game
+                .players
+                .iter()
+                .map(|player| player.pairs.clone())
+                .collect(),
+            scores: game.players.iter().map(|player| player.score).collect(),
+            deck_size: game.deck.len(),
+            asks: game.asks.clone(),
+            went_out: game.went_out.clone(),
+            dealer_index: game.dealer_index,
+            turns: game.turns,
+            game_modes: game.players.iter().map(|player| player.game_mode).collect(),
+            rules: game.rules.clone(),
+        }
+    }
+
+    pub fn player_count(&self) -> usize {
+        self.hand_sizes.len()
+    }
+
+    /// Get the ranks every player is known to hold, by player index
+    ///
+    /// A player asking for a rank holds a card of it. They are no longer known
+    /// to hold it once they lay down cards of the rank or are asked for it and
+    /// hand a card over.
+    pub fn known_ranks(&self) -> Vec<Vec<Rank>> {
+        let mut known: Vec<Vec<Rank>> = vec![Vec::new(); self.player_count()];
+        for ask in &self.asks {
+            if ask.received > 0 {
+                known[ask.asked].retain(|rank| *rank != ask.card.rank);
+            }
+            if !known[ask.asker].contains(&ask.card.rank) {
+                known[ask.asker].push(ask.card.rank);
+            }
+            for card in &ask.laid_down {
+                known[ask.asker].retain(|rank| *rank != card.rank);
+            }
+        }
+        for (index, ranks) in known.iter_mut().enumerate() {
+            if self.hand_sizes[index] == 0 {
+                ranks.clear();
+            }
+        }
+        known
+    }
+
+    /// Get the cards the player has not seen, the other hands and the stock together
+    pub fn unseen_cards(&self) -> Vec<Card> {
+        let mut unseen = self.rules.deck.build();
+        let seen = self
+            .pairs
+            .iter()
+            .flat_map(|pile| pile.cards().iter())
+            .chain(self.hand.cards().iter());
+        for card in seen {
+            unseen.remove_card(card);
+        }
+        unseen.cards().clone()
+    }
+
+    /// Sample a full game consistent with the view
+    ///
+    /// The unseen cards are shuffled and dealt to the other players and the stock.
+    /// Every player gets a card of each rank they are known to hold first, and no
+    /// player is dealt a complete book since they would have laid it down.
+    pub fn determinize<R: Rng + ?Sized>(&self, rng: &mut R) -> GameState {
+        let mut unseen = self.unseen_cards();
+        unseen.shuffle(rng);
+
+        let player_count = self.player_count();
+        let mut hands: Vec<Vec<Card>> = vec![Vec::new(); player_count];
+        hands[self.player_index] = self.hand.cards().clone();
+        let others: Vec<usize> = (0..player_count)
+            .filter(|&index| index != self.player_index)
+            .collect();
+
+        for (index, ranks) in self.known_ranks().into_iter().enumerate() {
+            if index == self.player_index {
+                continue;
+            }
+            for rank in ranks {
+                let position = unseen.iter().position(|card| card.rank == rank);
+                if let (Some(position), true) =
+                    (position, hands[index].len() < self.hand_sizes[index])
+                {
+                    hands[index].push(unseen.remove(position));
+                }
+            }
+        }
+
+        let book_size = self.rules.book_size;
+        let mut deck = Vec::new();
+        for card in unseen {
+            let completes_book = |hand: &Vec<Card>| {
+                hand.iter()
+                    .filter(|other| pairing_rank(other) == pairing_rank(&card))
+                    .count()
+                    + 1
+                    >= book_size
+            };
+            match others.iter().find(|&&index| {
+                hands[index].len() < self.hand_sizes[index] && !completes_book(&hands[index])
+            }) {
+                Some(&index) => hands[index].push(card),
+                None => deck.push(card),
+            }
+        }
+
+        // the stock can only be too big if the hands could not be dealt without books
+        for &index in &others {
+            while hands[index].len() < self.hand_sizes[index] && deck.len() > self.deck_size {
+                hands[index].push(deck.remove(0));
+            }
+        }
+
+        GameState::from_view(
+            self,
+            hands.into_iter().map(Pile::from_vector).collect(),
+            Pile::from_vector(deck),
+            StdRng::seed_from_u64(rng.gen()),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cardpack::Standard52;
+
+    fn setup() -> GameState {
+        let mut game = GameState::new_with_rules(
+            vec![GameMode::Random; 3],
+            Rules::default(),
+            StdRng::seed_from_u64(6),
+        );
+        game.verbose = false;
+        game.deal();
+        game.players_match_cards();
+        game
+    }
+
+    #[test]
+    fn test_view_hides_other_hands() {
+        let game = setup();
+        let view = PlayerView::from_game(&game, 1);
+
+        assert_eq!(view.hand, game.players[1].hand);
+        assert_eq!(view.hand_sizes[0], game.players[0].hand.len());
+        assert_eq!(view.deck_size, game.deck.len());
+        assert_eq!(
+            view.unseen_cards().len(),
+            game.deck.len() + game.players[0].hand.len() + game.players[2].hand.len()
+        );
+    }
+
+    #[test]
+    fn test_known_ranks_follow_the_asks() {
+        let mut game = setup();
+        game.players[0].hand = Standard52::pile_from_index("5C 9S").unwrap().into();
+        game.players[1].hand = Standard52::pile_from_index("KS 9D").unwrap().into();
+        game.players[2].hand = Standard52::pile_from_index("QS").unwrap().into();
+        game.deck = Standard52::pile_from_index("2H 3H").unwrap();
+
+        // player 0 asks player 1 for fives and goes fishing, then player 1 asks
+        // player 0 for nines and lays the pair down
+        game.perform_turn(0, 1, 0);
+        game.perform_turn(1, 0, 1);
+
+        let known = PlayerView::from_game(&game, 2).known_ranks();
+        assert_eq!(known[0], vec![Standard52::card_from_index("5C").rank]);
+        assert!(known[1].is_empty());
+        assert!(game.asks[1].laid_down.len() == 2 && game.asks[1].received == 1);
+    }
+
+    #[test]
+    fn test_determinize_is_consistent_with_the_view() {
+        let mut game = setup();
+        game.perform_turn(0, 1, 0);
+        game.perform_turn(2, 0, 0);
+
+        let view = PlayerView::from_game(&game, 1);
+        let known = view.known_ranks();
+        let mut rng = StdRng::seed_from_u64(1);
+        for _ in 0..10 {
+            let sample = view.determinize(&mut rng);
+            let hand_sizes: Vec<usize> = sample.players.iter().map(|p| p.hand.len()).collect();
+            assert_eq!(hand_sizes, view.hand_sizes);
+            assert_eq!(sample.deck.len(), view.deck_size);
+            assert_eq!(sample.players[1].hand, game.players[1].hand);
+            for (index, ranks) in known.iter().enumerate() {
+                let sampled_ranks = sample.players[index].hand.ranks();
+                assert!(ranks.iter().all(|rank| sampled_ranks.contains(rank)));
+            }
+            assert!(sample
+                .players
+                .iter()
+                .all(|player| crate::game::find_pair(&player.hand.to_pile()).is_none()));
+        }
+    }
+}
@@ -0,0 +1,198 @@
+//! Property tests: games dealt from arbitrary decks, tables and strategies keep
+//! the engine's invariants
+
+use cardpack::{Pile, Rank};
+use proptest::prelude::*;
+use proptest::sample::subsequence;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use rust_go_fish::audit::check_invariants;
+use rust_go_fish::env::{EnvConfig, GoFishEnv};
+use rust_go_fish::rules::EmptyHand;
+use rust_go_fish::tracker::CardTracker;
+use rust_go_fish::{try_run_game, DeckConfig, EndGameCondition, GameMode, GameState, Rules};
+
+/// Turn limit for tables with sequential players, who can ask the same
+/// question forever once the stock is empty. `Rules::validate` refuses
+/// sequential play without a turn limit for the same reason.
+const TURN_LIMIT: u32 = 500;
+
+/// Turns a table of random players is given to finish on its own
+const RANDOM_TURN_BOUND: u32 = 100_000;
+
+fn game_mode() -> impl Strategy<Value = GameMode> {
+    prop_oneof![Just(GameMode::Random), Just(GameMode::Sequential)]
+}
+
+fn deck_config() -> impl Strategy<Value = DeckConfig> {
+    let ranks: Vec<Rank> = Pile::french_deck().ranks();
+    (1..=2usize, subsequence(ranks, 0..=8), any::<bool>()).prop_map(
+        |(decks, removed_ranks, jokers)| DeckConfig {
+            decks,
+            removed_ranks,
+            jokers,
+        },
+    )
+}
+
+fn empty_hand() -> impl Strategy<Value = EmptyHand> {
+    prop_oneof![
+        Just(EmptyHand::EndGame),
+        Just(EmptyHand::Draw),
+        Just(EmptyHand::SitOut)
+    ]
+}
+
+/// Rules without wild cards or families, so every book scores one point
+fn rules() -> impl Strategy<Value = Rules> {
+    (
+        deck_config(),
+        prop_oneof![Just(2usize), Just(4usize)],
+        1..=7usize,
+        empty_hand(),
+        any::<bool>(),
+        any::<bool>(),
+    )
+        .prop_map(
+            |(deck, book_size, hand_size, empty_hand, surrender_all, fish_your_wish)| Rules {
+                deck,
+                book_size,
+                hand_size,
+                empty_hand,
+                surrender_all,
+                fish_your_wish,
+                ..Rules::default()
+            },
+        )
+        .prop_filter("the rules must be valid", |rules| rules.validate().is_ok())
+}
+
+/// Deal a game in audit mode
+fn deal(rules: Rules, game_modes: Vec<GameMode>, seed: u64) -> GameState {
+    let mut game = GameState::new_with_rules(game_modes, rules, StdRng::seed_from_u64(seed));
+    game.verbose = false;
+    game.audit = true;
+    game.deal();
+    game.players_match_cards();
+    game
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(64))]
+
+    #[test]
+    fn games_keep_their_invariants(
+        mut rules in rules(),
+        game_modes in prop::collection::vec(game_mode(), 2..=6),
+        seed in any::<u64>(),
+    ) {
+        rules.turn_limit = Some(TURN_LIMIT);
+        let mut game = deal(rules, game_modes, seed);
+        prop_assert_eq!(check_invariants(&game), Ok(()));
+
+        let standings = try_run_game(&mut game);
+        prop_assert!(standings.is_ok(), "{:?}", standings.err());
+        prop_assert_ne!(standings.unwrap().outcome, EndGameCondition::Continue);
+        prop_assert_eq!(check_invariants(&game), Ok(()));
+
+        let book_size = game.rules.book_size;
+        for player in &game.players {
+            prop_assert_eq!(player.score as usize, player.pairs.len() / book_size);
+        }
+    }
+
+    #[test]
+    fn the_winners_have_the_highest_score(
+        mut rules in rules(),
+        game_modes in prop::collection::vec(game_mode(), 2..=6),
+        seed in any::<u64>(),
+    ) {
+        rules.turn_limit = Some(TURN_LIMIT);
+        let mut game = deal(rules, game_modes, seed);
+        game.audit = false;
+        let outcome = try_run_game(&mut game).unwrap().outcome;
+
+        let best = game.players.iter().map(|player| player.score).max().unwrap();
+        let winners = match outcome {
+            EndGameCondition::Winner(winner) => vec![winner],
+            EndGameCondition::Tie(winners) => winners,
+            EndGameCondition::Continue => Vec::new(),
+        };
+        prop_assert!(!winners.is_empty());
+        for winner in winners {
+            prop_assert_eq!(game.players[winner].score, best);
+        }
+    }
+
+    /// Only random players are checked: a table of sequential players can cycle
+    /// through the same asks once the stock is empty and only ends at its turn
+    /// limit, which `sequential_play_needs_a_turn_limit` checks the rules demand
+    #[test]
+    fn random_games_end_on_their_own(
+        rules in rules(),
+        player_count in 2..=6usize,
+        seed in any::<u64>(),
+    ) {
+        let mut rules = rules;
+        rules.turn_limit = Some(RANDOM_TURN_BOUND);
+        let mut game = deal(rules, vec![GameMode::Random; player_count], seed);
+        game.audit = false;
+        try_run_game(&mut game).unwrap();
+        prop_assert!(game.turns < RANDOM_TURN_BOUND);
+    }
+
+    #[test]
+    fn sequential_play_needs_a_turn_limit(mut rules in rules()) {
+        rules.game_mode = GameMode::Sequential;
+        prop_assert!(rules.validate().is_err());
+        rules.turn_limit = Some(TURN_LIMIT);
+        prop_assert!(rules.validate().is_ok());
+    }
+
+    #[test]
+    fn env_episodes_follow_the_action_mask(
+        player_count in 2..=5usize,
+        seed in any::<u64>(),
+        choices in prop::collection::vec(any::<u8>(), 1..200),
+    ) {
+        let mut env = GoFishEnv::new(EnvConfig::new(player_count));
+        let mut observation = env.reset(seed);
+        for choice in choices {
+            let legal: Vec<usize> = (0..observation.action_mask.len())
+                .filter(|&action| observation.action_mask[action])
+                .collect();
+            prop_assert!(!legal.is_empty());
+            let (next, reward, done) = env.step(legal[choice as usize % legal.len()]);
+            prop_assert_eq!(check_invariants(&env.game), Ok(()));
+            if done {
+                prop_assert!((0.0..=1.0).contains(&reward));
+                break;
+            }
+            observation = next;
+        }
+    }
+
+    #[test]
+    fn trackers_count_every_hand(
+        mut rules in rules(),
+        game_modes in prop::collection::vec(game_mode(), 2..=5),
+        seed in any::<u64>(),
+        turns in 0..60u32,
+    ) {
+        rules.turn_limit = Some(turns);
+        let mut game = deal(rules, game_modes, seed);
+        game.audit = false;
+        try_run_game(&mut game).unwrap();
+
+        let tracker = CardTracker::from_game(&game, Some(0));
+        prop_assert_eq!(tracker.stock_size, game.deck.len());
+        let expected = tracker.expected_counts();
+        for (index, player) in game.players.iter().enumerate() {
+            prop_assert_eq!(tracker.hand_sizes[index], player.hand.len());
+            if index > 0 {
+                let total: f64 = expected[index].iter().sum();
+                prop_assert!((total - player.hand.len() as f64).abs() < 1e-3);
+            }
+        }
+    }
+}
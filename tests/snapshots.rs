@@ -0,0 +1,221 @@
+//! Golden transcript tests: seeded games of every rule variant and strategy are
+//! played to the end and their transcripts compared with the files in
+//! `tests/snapshots`. Run with `BLESS=1` to write the current transcripts as
+//! the new golden files.
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use rust_go_fish::rules::EmptyHand;
+use rust_go_fish::{run_game, DeckConfig, GameMode, GameState, Rules, WildCards, WildRules};
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+/// Lines of unchanged transcript shown around every change
+const DIFF_CONTEXT: usize = 3;
+
+/// A seeded game whose transcript is kept as a golden file
+struct Snapshot {
+    name: &'static str,
+    rules: Rules,
+    game_modes: Vec<GameMode>,
+    seed: u64,
+}
+
+fn preset(name: &str) -> Rules {
+    Rules::preset(name).unwrap()
+}
+
+fn wild_jokers() -> Rules {
+    Rules {
+        deck: DeckConfig {
+            jokers: true,
+            ..DeckConfig::default()
+        },
+        wild: Some(WildRules::new(WildCards::Jokers)),
+        empty_hand: EmptyHand::SitOut,
+        turn_limit: Some(1000),
+        ..Rules::default()
+    }
+}
+
+fn snapshots() -> Vec<Snapshot> {
+    use GameMode::{Random, Search, Sequential};
+    let short = Rules {
+        turn_limit: Some(12),
+        ..Rules::default()
+    };
+    vec![
+        Snapshot {
+            name: "standard-random",
+            rules: preset("standard"),
+            game_modes: vec![Random; 4],
+            seed: 1,
+        },
+        Snapshot {
+            name: "standard-sequential",
+            rules: preset("standard"),
+            game_modes: vec![Sequential; 3],
+            seed: 2,
+        },
+        Snapshot {
+            name: "standard-search",
+            rules: short,
+            game_modes: vec![Search, Random],
+            seed: 3,
+        },
+        Snapshot {
+            name: "books-random",
+            rules: preset("books"),
+            game_modes: vec![Random; 4],
+            seed: 4,
+        },
+        Snapshot {
+            name: "fish-your-wish-random",
+            rules: preset("fish-your-wish"),
+            game_modes: vec![Random; 5],
+            seed: 5,
+        },
+        Snapshot {
+            name: "happy-families-random",
+            rules: preset("happy-families"),
+            game_modes: vec![Random; 4],
+            seed: 6,
+        },
+        Snapshot {
+            name: "happy-families-sequential",
+            rules: preset("happy-families"),
+            game_modes: vec![Sequential, Random, Sequential],
+            seed: 7,
+        },
+        Snapshot {
+            name: "wild-jokers-random",
+            rules: wild_jokers(),
+            game_modes: vec![Random; 3],
+            seed: 8,
+        },
+    ]
+}
+
+/// Play the game and render its transcript with the final standings
+fn play(snapshot: &Snapshot) -> String {
+    let mut game = GameState::new_with_rules(
+        snapshot.game_modes.clone(),
+        snapshot.rules.clone(),
+        StdRng::seed_from_u64(snapshot.seed),
+    );
+    game.verbose = false;
+    game.audit = true;
+    game.deal();
+    game.players_match_cards();
+    let standings = run_game(&mut game);
+
+    let modes: Vec<String> = snapshot
+        .game_modes
+        .iter()
+        .map(|mode| mode.to_string())
+        .collect();
+    format!(
+        "{} seed {} players {}\n\n{}\n{}",
+        snapshot.name,
+        snapshot.seed,
+        modes.join(","),
+        game.transcript(),
+        standings
+    )
+}
+
+/// Get a line diff of two texts, unchanged lines far from a change left out
+fn diff(expected: &str, actual: &str) -> String {
+    let old: Vec<&str> = expected.lines().collect();
+    let new: Vec<&str> = actual.lines().collect();
+
+    // longest common subsequence lengths of every pair of suffixes
+    let mut common = vec![vec![0; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            common[i][j] = if old[i] == new[j] {
+                common[i + 1][j + 1] + 1
+            } else {
+                common[i + 1][j].max(common[i][j + 1])
+            };
+        }
+    }
+
+    let mut lines: Vec<(char, &str)> = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            lines.push((' ', old[i]));
+            i += 1;
+            j += 1;
+        } else if i < old.len() && (j == new.len() || common[i + 1][j] >= common[i][j + 1]) {
+            lines.push(('-', old[i]));
+            i += 1;
+        } else {
+            lines.push(('+', new[j]));
+            j += 1;
+        }
+    }
+
+    let changed: Vec<usize> = (0..lines.len()).filter(|&n| lines[n].0 != ' ').collect();
+    let near_change = |n: usize| {
+        changed
+            .iter()
+            .any(|&c| n + DIFF_CONTEXT >= c && n <= c + DIFF_CONTEXT)
+    };
+    let mut output = String::new();
+    let mut skipped = false;
+    for (n, (marker, line)) in lines.iter().enumerate() {
+        if near_change(n) {
+            output.push_str(&format!("{} {}\n", marker, line));
+            skipped = false;
+        } else if !skipped {
+            output.push_str("...\n");
+            skipped = true;
+        }
+    }
+    output
+}
+
+#[test]
+fn transcripts_match_the_snapshots() {
+    let directory = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/snapshots");
+    let bless = env::var("BLESS").is_ok_and(|value| value == "1");
+    let mut failures = Vec::new();
+
+    for snapshot in snapshots() {
+        let actual = play(&snapshot);
+        let path = directory.join(format!("{}.txt", snapshot.name));
+        if bless {
+            fs::create_dir_all(&directory).unwrap();
+            fs::write(&path, &actual).unwrap();
+            continue;
+        }
+        match fs::read_to_string(&path) {
+            Ok(expected) if expected == actual => {}
+            Ok(expected) => failures.push(format!(
+                "{} changed:\n{}",
+                snapshot.name,
+                diff(&expected, &actual)
+            )),
+            Err(error) => failures.push(format!("{}: {}", path.display(), error)),
+        }
+    }
+
+    assert!(
+        failures.is_empty(),
+        "{}\nRun `BLESS=1 cargo test --test snapshots` to accept the new transcripts",
+        failures.join("\n")
+    );
+}
+
+#[test]
+fn diff_shows_changed_lines_with_context() {
+    let expected = "a\nb\nc\nd\ne\nf\ng\nh\ni\n";
+    let actual = "a\nb\nc\nd\ne\nF\ng\nh\ni\n";
+    assert_eq!(
+        diff(expected, actual),
+        "...\n  c\n  d\n  e\n- f\n+ F\n  g\n  h\n  i\n"
+    );
+}